@@ -49,7 +49,7 @@ fn main() {
     .to_string();
 
     let mut file_writer = BufWriter::new(File::create("/tmp/example-pot_pop_fr.geojson").unwrap());
-    file_writer.write(&geojson_str.as_bytes()).unwrap();
+    file_writer.write_all(geojson_str.as_bytes()).unwrap();
 
     // Second example from the volcano dataset
     let volcano = include_str!("../tests/fixtures/volcano.json");
@@ -86,5 +86,5 @@ fn main() {
     .to_string();
 
     let mut file_writer = BufWriter::new(File::create("/tmp/example-volcano.geojson").unwrap());
-    file_writer.write(&geojson_str.as_bytes()).unwrap();
+    file_writer.write_all(geojson_str.as_bytes()).unwrap();
 }
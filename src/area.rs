@@ -18,3 +18,37 @@ pub(crate) fn area(ring: &[Coord<f64>]) -> f64 {
     }
     area
 }
+
+/// Clip a ring (that doesn't need to be closed) to the rectangle
+/// `[min_x, min_y, max_x, max_y]`, using the Sutherland–Hodgman algorithm.
+pub(crate) fn clip_ring(ring: &[Coord<f64>], rect: [f64; 4]) -> Vec<Coord<f64>> {
+    let mut ring = ring.to_vec();
+    // For each side of the rectangle: the axis (0 for x, 1 for y),
+    // the limit, and whether the points to keep are below this limit
+    for (axis, limit, keep_below) in [
+        (0, rect[0], false),
+        (0, rect[2], true),
+        (1, rect[1], false),
+        (1, rect[3], true),
+    ] {
+        let coordinate = |p: &Coord<f64>| if axis == 0 { p.x } else { p.y };
+        let inside =
+            |p: &Coord<f64>| (coordinate(p) <= limit) == keep_below || coordinate(p) == limit;
+        let input = std::mem::take(&mut ring);
+        for (i, current) in input.iter().enumerate() {
+            let previous = &input[(i + input.len() - 1) % input.len()];
+            if inside(current) != inside(previous) {
+                let t =
+                    (limit - coordinate(previous)) / (coordinate(current) - coordinate(previous));
+                ring.push(Coord {
+                    x: previous.x + t * (current.x - previous.x),
+                    y: previous.y + t * (current.y - previous.y),
+                });
+            }
+            if inside(current) {
+                ring.push(*current);
+            }
+        }
+    }
+    ring
+}
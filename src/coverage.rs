@@ -0,0 +1,282 @@
+use crate::area::{area, clip_ring};
+use crate::errors::Result;
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::isobands::{Cell, Edge, Pt, Settings};
+use crate::shape_coordinates::prepare_cell;
+use geo_types::Coord;
+
+/// The corners of a cell, at positions 1, 2, 3 and 4 (or 0) of its boundary.
+static CORNERS: [Pt; 4] = [Pt(0., 1.), Pt(1., 1.), Pt(1., 0.), Pt(0., 0.)];
+
+/// The quarters of a cell, as `[min_x, min_y, max_x, max_y]`, each one belonging
+/// to the pixel centered on the corresponding corner of the cell (`x0`, `x1`, `x2` and `x3`).
+static QUARTERS: [([f64; 4], (usize, usize)); 4] = [
+    ([0., 0., 0.5, 0.5], (0, 0)),
+    ([0.5, 0., 1., 0.5], (1, 0)),
+    ([0.5, 0.5, 1., 1.], (1, 1)),
+    ([0., 0.5, 0.5, 1.], (0, 1)),
+];
+
+/// Statistics of the values of a secondary grid inside an isoband
+/// (see [`ContourBuilder::zonal_stats`](crate::ContourBuilder::zonal_stats)).
+///
+/// Each value is weighted by the fraction of its pixel (the area centered on the point
+/// of the grid, clipped to the extent of the grid) that is covered by the band.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZonalStats {
+    /// The minimum value of the band
+    pub min_v: f64,
+    /// The maximum value of the band
+    pub max_v: f64,
+    /// The number of pixels inside the band, each pixel being weighted
+    /// by the fraction of its area covered by the band
+    pub count: f64,
+    /// The weighted sum of the values inside the band
+    pub sum: f64,
+    /// The weighted mean of the values inside the band (NaN if the band is empty)
+    pub mean: f64,
+    /// The minimum value of the pixels (at least partly) inside the band (NaN if the band is empty)
+    pub min: f64,
+    /// The maximum value of the pixels (at least partly) inside the band (NaN if the band is empty)
+    pub max: f64,
+}
+
+/// The fraction of each cell of the grid covered by an isoband
+/// (see [`ContourBuilder::cell_coverage`](crate::ContourBuilder::cell_coverage)).
+///
+/// The cells are the squares between four adjacent points of the grid
+/// (`width - 1` columns and `height - 1` rows of cells for a grid of `width` x `height` points),
+/// the corners of the cell `(x, y)` being the points `(x, y)`, `(x + 1, y)`, `(x, y + 1)` and `(x + 1, y + 1)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandCoverage {
+    /// The minimum value of the band
+    pub min_v: f64,
+    /// The maximum value of the band
+    pub max_v: f64,
+    /// The number of columns of cells
+    pub width: usize,
+    /// The number of rows of cells
+    pub height: usize,
+    /// The fraction (between 0 and 1) of each cell covered by the band, row by row
+    pub fractions: Vec<f64>,
+}
+
+impl BandCoverage {
+    /// Get the fraction of the cell `(x, y)` covered by the band.
+    pub fn fraction(&self, x: usize, y: usize) -> f64 {
+        self.fractions[y * self.width + x]
+    }
+}
+
+pub(crate) fn _cell_coverage<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+) -> Result<Vec<BandCoverage>> {
+    let width = data.width().saturating_sub(1);
+    let height = data.height().saturating_sub(1);
+
+    bands
+        .iter()
+        .map(|&(ref opt, min_v, max_v)| {
+            let mut fractions = vec![0.; width * height];
+            for_each_cell_polygon(data, opt, |x, y, polygon| {
+                fractions[y * width + x] += polygon_area(polygon);
+            })?;
+            Ok(BandCoverage {
+                min_v,
+                max_v,
+                width,
+                height,
+                fractions,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn _zonal_stats<T: GridValue, U: GridValue>(
+    data: &BorrowedGrid<T>,
+    values: &[U],
+    bands: &[(Settings, f64, f64)],
+) -> Result<Vec<ZonalStats>> {
+    let (width, height) = (data.width(), data.height());
+    let mut weights = vec![0.; width * height];
+
+    bands
+        .iter()
+        .map(|&(ref opt, min_v, max_v)| {
+            pixel_coverage(data, opt, &mut weights)?;
+
+            let mut stats = ZonalStats {
+                min_v,
+                max_v,
+                count: 0.,
+                sum: 0.,
+                mean: f64::NAN,
+                min: f64::NAN,
+                max: f64::NAN,
+            };
+            for (&weight, value) in weights.iter().zip(values.iter()) {
+                let value = value.to_f64();
+                if weight <= 0. || value.is_nan() {
+                    continue;
+                }
+                stats.count += weight;
+                stats.sum += weight * value;
+                stats.min = stats.min.min(value);
+                stats.max = stats.max.max(value);
+            }
+            if stats.count > 0. {
+                stats.mean = stats.sum / stats.count;
+            }
+            Ok(stats)
+        })
+        .collect()
+}
+
+/// Fill `weights` with the fraction of the pixel of each point of the grid
+/// that is covered by the band (the pixels on the border of the grid
+/// being clipped to the extent of the grid, i.e. to the cells around their point).
+fn pixel_coverage<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    opt: &Settings,
+    weights: &mut [f64],
+) -> Result<()> {
+    let (width, height) = (data.width(), data.height());
+    weights.iter_mut().for_each(|w| *w = 0.);
+
+    for_each_cell_polygon(data, opt, |x, y, polygon| {
+        for (quarter, (dx, dy)) in QUARTERS.iter() {
+            weights[(y + dy) * width + x + dx] += clipped_area(polygon, quarter);
+        }
+    })?;
+
+    // Each cell around a point covers a quarter of its pixel
+    for y in 0..height {
+        for x in 0..width {
+            let n_x = (x > 0) as usize + (x + 1 < width) as usize;
+            let n_y = (y > 0) as usize + (y + 1 < height) as usize;
+            if n_x * n_y > 0 {
+                weights[y * width + x] /= 0.25 * (n_x * n_y) as f64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Call `f` with each polygon covered by the band in each cell of the grid
+/// (with the coordinates of the cell, the polygon being in the coordinates of the cell).
+fn for_each_cell_polygon<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    opt: &Settings,
+    mut f: impl FnMut(usize, usize, &[Pt]),
+) -> Result<()> {
+    for y in 0..data.height().saturating_sub(1) {
+        for x in 0..data.width().saturating_sub(1) {
+            if let Some(cell) = prepare_cell(x, y, data, opt)? {
+                for polygon in cell_polygons(&cell, opt) {
+                    f(x, y, &polygon);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Position of a point of the boundary of a cell, going clockwise
+/// from its bottom left corner (between 0 and 4).
+fn boundary_position(p: &Pt) -> f64 {
+    if p.0 == 0. {
+        p.1
+    } else if p.1 == 1. {
+        1. + p.0
+    } else if p.0 == 1. {
+        3. - p.1
+    } else {
+        4. - p.0
+    }
+}
+
+/// Rebuilds the shape polygons (triangles, tetragons, ..., octagons) covered by the band
+/// in a cell (in the coordinates of the cell, between 0 and 1) from the edges of the cell.
+///
+/// As the band is on the right of the edges, the polygons are obtained by following
+/// each edge, then the boundary of the cell clockwise until the start of the next edge.
+pub(crate) fn cell_polygons(cell: &Cell, opt: &Settings) -> Vec<Vec<Pt>> {
+    if cell.edges.is_empty() {
+        // The cell is either fully inside or fully outside the band
+        return if cell.x0 >= opt.min_v && cell.x0 <= opt.max_v {
+            vec![CORNERS.to_vec()]
+        } else {
+            vec![]
+        };
+    }
+
+    let mut edges = cell
+        .edges
+        .values()
+        .map(|edge| {
+            (
+                boundary_position(&edge.path[0]),
+                boundary_position(&edge.path[1]),
+                edge,
+            )
+        })
+        .collect::<Vec<_>>();
+    let edge_points = |edge: &Edge| {
+        std::iter::once(&edge.path[0])
+            .chain(edge.via.as_ref())
+            .chain(std::iter::once(&edge.path[1]))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let mut polygons = Vec::new();
+    while let Some((start, mut end, edge)) = edges.pop() {
+        let mut polygon = edge_points(edge);
+        loop {
+            let distance = |position: f64| (position - end).rem_euclid(4.);
+            let next = edges
+                .iter()
+                .enumerate()
+                .map(|(i, edge)| (i, distance(edge.0)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let (d, next) = match next {
+                Some((i, d)) if d < distance(start) => (d, Some(i)),
+                _ => (distance(start), None),
+            };
+
+            // Add the corners of the cell between the end of the current edge
+            // and the start of the next one
+            let mut corner = end.floor() + 1.;
+            while corner < end + d {
+                polygon.push(CORNERS[(corner as usize - 1) % 4].clone());
+                corner += 1.;
+            }
+
+            match next {
+                Some(i) => {
+                    let (_, next_end, next_edge) = edges.swap_remove(i);
+                    polygon.extend(edge_points(next_edge));
+                    end = next_end;
+                }
+                None => break,
+            }
+        }
+        polygons.push(polygon);
+    }
+    polygons
+}
+
+/// Area of a polygon.
+fn polygon_area(polygon: &[Pt]) -> f64 {
+    area(&to_coords(polygon)).abs() / 2.
+}
+
+/// Area of the part of a polygon inside a rectangle (`[min_x, min_y, max_x, max_y]`).
+fn clipped_area(polygon: &[Pt], rect: &[f64; 4]) -> f64 {
+    area(&clip_ring(&to_coords(polygon), *rect)).abs() / 2.
+}
+
+fn to_coords(polygon: &[Pt]) -> Vec<Coord<f64>> {
+    polygon.iter().map(|p| Coord { x: p.0, y: p.1 }).collect()
+}
@@ -37,8 +37,16 @@ pub enum ErrorKind {
     BadIntervals,
     BadData,
     PolygonReconstructionError,
+    BadClassification(&'static str),
+    BadCoordinates,
+    #[cfg(feature = "mvt")]
+    BadTile,
     #[cfg(feature = "geojson")]
     JsonError(serde_json::error::Error),
+    #[cfg(feature = "wkt")]
+    WktError(String),
+    #[cfg(feature = "wkb")]
+    WkbError(String),
 }
 
 #[cfg(feature = "geojson")]
@@ -71,8 +79,16 @@ impl fmt::Display for Error {
             ErrorKind::BadIntervals => write!(f, "Intervals argument must have at least 2 elements (representing the lower-bound and the upper-bound of the band to compute)"),
             ErrorKind::BadData => write!(f, "Data must have at least some values"),
             ErrorKind::PolygonReconstructionError => write!(f, "Error while reconstructing the polygons from rings (this is a bug, please report it)"),
+            ErrorKind::BadClassification(reason) => write!(f, "Thresholds can't be computed: {}", reason),
+            ErrorKind::BadCoordinates => write!(f, "The coordinates of the rows and columns must be sorted (in ascending or descending order)"),
+            #[cfg(feature = "mvt")]
+            ErrorKind::BadTile => write!(f, "The zoom level of the tile must be at most 32, and its column and row lower than 2^z"),
             #[cfg(feature = "geojson")]
             ErrorKind::JsonError(ref err) => err.fmt(f),
+            #[cfg(feature = "wkt")]
+            ErrorKind::WktError(ref err) => write!(f, "Invalid WKT: {}", err),
+            #[cfg(feature = "wkb")]
+            ErrorKind::WkbError(ref err) => write!(f, "Invalid WKB: {}", err),
         }
     }
 }
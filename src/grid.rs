@@ -1,5 +1,37 @@
 pub(crate) type GridCoord = (usize, usize);
 
+/// A numeric type that can be used as a value of the input grid.
+///
+/// Values are converted to `f64` when they are read from the grid,
+/// so that the grid doesn't have to be copied (and widened) beforehand.
+/// This trait is implemented for all the primitive integer and floating point types.
+pub trait GridValue: Copy + PartialEq + Send + Sync {
+    /// Convert the value to `f64`.
+    fn to_f64(self) -> f64;
+    /// Convert a `f64` to a value of this type (as an `as` cast would).
+    fn from_f64(v: f64) -> Self;
+}
+
+macro_rules! impl_grid_value {
+    ($($t:ty),*) => {
+        $(
+            impl GridValue for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                #[inline]
+                fn from_f64(v: f64) -> Self {
+                    v as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_grid_value!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
 // pub(crate) trait GridTrait<T> {
 //     fn width(&self) -> usize;
 //     fn height(&self) -> usize;
@@ -112,6 +144,8 @@ pub(crate) struct BorrowedGrid<'a, T> {
     array: &'a [T],
     width: usize,
     height: usize,
+    nodata: Option<T>,
+    mask: Option<&'a [bool]>,
 }
 
 impl<'a, T> BorrowedGrid<'a, T> {
@@ -122,11 +156,16 @@ impl<'a, T> BorrowedGrid<'a, T> {
             array,
             width,
             height,
+            nodata: None,
+            mask: None,
         }
     }
 
-    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
-        self.array.chunks(self.width)
+    /// Set the validity mask of the grid (`false` marks missing data).
+    /// As for the values, we already checked that the mask was of size width * height.
+    pub fn with_mask(mut self, mask: Option<&'a [bool]>) -> Self {
+        self.mask = mask;
+        self
     }
 
     pub fn width(&self) -> usize {
@@ -150,6 +189,43 @@ impl<'a, T> BorrowedGrid<'a, T> {
     }
 }
 
+impl<'a, T: GridValue> BorrowedGrid<'a, T> {
+    /// Set the value that marks missing data in the grid
+    /// (converted to the type of the values, so that it is compared in this type).
+    pub fn with_nodata(mut self, nodata: Option<f64>) -> Self {
+        self.nodata = nodata.map(T::from_f64);
+        self
+    }
+
+    /// Get the value at the given coordinates, converted to `f64`
+    /// (coordinates are not checked, see `get_value` for a checked version).
+    /// Missing values (NaN, no-data value or masked value) are returned as NaN.
+    #[inline]
+    pub fn value(&self, p: GridCoord) -> f64 {
+        let v = self[p];
+        if self.nodata == Some(v)
+            || self
+                .mask
+                .is_some_and(|mask| !unsafe { *mask.get_unchecked(p.1 * self.width + p.0) })
+        {
+            f64::NAN
+        } else {
+            v.to_f64()
+        }
+    }
+
+    /// Get the value at the given coordinates, converted to `f64`,
+    /// or `None` if the coordinates are outside the grid.
+    #[inline]
+    pub fn get_value(&self, p: &GridCoord) -> Option<f64> {
+        if self.has(p) {
+            Some(self.value(*p))
+        } else {
+            None
+        }
+    }
+}
+
 impl<'a, T> std::ops::Index<GridCoord> for BorrowedGrid<'a, T> {
     type Output = T;
 
@@ -0,0 +1,227 @@
+use crate::area::area;
+use crate::errors::Result;
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::isobands::{Band, Cell, ContourBuilder, Settings};
+use crate::polygons::{trace_ring, LazyCells, START_EDGES};
+use geo_types::{LineString, Point};
+
+/// The part of the grid whose rings are traced again after a change of the values
+/// of the points `(x, y)` such that `min_x <= x < max_x` and `min_y <= y < max_y`.
+pub(crate) struct DirtyRegion {
+    /// The extent (`[min_x, min_y, max_x, max_y]`, in output coordinates) of the cells
+    /// that may be modified by the change, with a margin of one cell (so that the
+    /// transformation of the coordinates can't move their points out of it):
+    /// the rings with a point in it are replaced by the rings traced again.
+    extent: [f64; 4],
+    /// The cells whose points may be in this extent, from which the rings are traced again
+    cells: Vec<(usize, usize)>,
+}
+
+impl DirtyRegion {
+    /// Computes the region modified by a change of the values in `dirty`
+    /// (`[min_x, min_y, max_x, max_y]`) on the grid of the builder (of at least 2 x 2 points).
+    pub(crate) fn new(builder: &ContourBuilder, dirty: [usize; 4]) -> DirtyRegion {
+        let (width, height) = (builder.width, builder.height);
+        let (cols, rows) = (width - 1, height - 1);
+        // The values whose state may have changed (the changed ones and their neighbours,
+        // the state of a missing value depending on its neighbours)
+        let values = [
+            dirty[0].saturating_sub(1),
+            dirty[1].saturating_sub(1),
+            (dirty[2] + 1).min(width),
+            (dirty[3] + 1).min(height),
+        ];
+        // The cells having one of these values as corner (the rings walking around the dropped
+        // ones go along their sides), and the points of these cells with a margin of one cell
+        let cells = [
+            values[0].saturating_sub(1),
+            values[1].saturating_sub(1),
+            values[2].min(cols),
+            values[3].min(rows),
+        ];
+        let nodes = [
+            cells[0].saturating_sub(1),
+            cells[1].saturating_sub(1),
+            (cells[2] + 1).min(cols),
+            (cells[3] + 1).min(rows),
+        ];
+        let mut points = (nodes[0]..=nodes[2])
+            .flat_map(|x| (nodes[1]..=nodes[3]).map(move |y| Point::new(x as f64, y as f64)))
+            .collect::<Vec<_>>();
+        builder.transform_points(&mut points);
+        let extent = bounds(&points);
+
+        let cells = if builder.is_transformed() {
+            // The cells whose points are in the extent of their corners
+            // (looked for in the whole grid, which may be curvilinear)
+            let tolerance = 1e-9
+                * (extent[2] - extent[0] + extent[3] - extent[1])
+                    .max(extent.iter().fold(0., |max: f64, v| max.max(v.abs())));
+            let row = |y: usize| {
+                let mut points = (0..width)
+                    .map(|x| Point::new(x as f64, y as f64))
+                    .collect::<Vec<_>>();
+                builder.transform_points(&mut points);
+                points
+            };
+            let mut cells = Vec::new();
+            let mut top = row(0);
+            for y in 0..rows {
+                let bottom = row(y + 1);
+                for x in 0..cols {
+                    let corners = bounds(&[top[x], top[x + 1], bottom[x], bottom[x + 1]]);
+                    if corners[0] <= extent[2] + tolerance
+                        && corners[2] >= extent[0] - tolerance
+                        && corners[1] <= extent[3] + tolerance
+                        && corners[3] >= extent[1] - tolerance
+                    {
+                        cells.push((x, y));
+                    }
+                }
+                top = bottom;
+            }
+            cells
+        } else {
+            // The cells touching the extent
+            (nodes[0].saturating_sub(1)..(nodes[2] + 1).min(cols))
+                .flat_map(|x| {
+                    (nodes[1].saturating_sub(1)..(nodes[3] + 1).min(rows)).map(move |y| (x, y))
+                })
+                .collect()
+        };
+        DirtyRegion { extent, cells }
+    }
+
+    /// Whether a ring (in output coordinates) has a point in the extent of the region.
+    fn contains(&self, ring: &LineString<f64>) -> bool {
+        let e = &self.extent;
+        ring.0
+            .iter()
+            .any(|c| c.x >= e[0] && c.x <= e[2] && c.y >= e[1] && c.y <= e[3])
+    }
+}
+
+/// The extent (`[min_x, min_y, max_x, max_y]`) of some points.
+fn bounds(points: &[Point<f64>]) -> [f64; 4] {
+    points.iter().fold(
+        [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ],
+        |e, p| {
+            [
+                e[0].min(p.x()),
+                e[1].min(p.y()),
+                e[2].max(p.x()),
+                e[3].max(p.y()),
+            ]
+        },
+    )
+}
+
+/// Updates a band (whose frame isn't modified by the change) by replacing its rings
+/// with a point in the dirty region by the rings traced again from the cells of the region.
+///
+/// The cells are prepared only when the traced rings go through them (`cell_grid` being
+/// left empty). Each ring is traced from the first of its edges in the order of
+/// [`trace_band_paths`](crate::polygons::trace_band_paths), so that it is
+/// the same as the one of a full computation.
+pub(crate) fn update_band<T: GridValue>(
+    builder: &ContourBuilder,
+    data: &BorrowedGrid<T>,
+    cell_grid: &mut [Vec<Option<Cell>>],
+    region: &DirtyRegion,
+    opt: &Settings,
+    previous: Band,
+) -> Result<Band> {
+    let mut lazy = LazyCells::default();
+    // The frame of the band being unchanged, whether a ring goes along it doesn't matter
+    let mut walked_frame = false;
+
+    // Find the rings going through the cells of the region,
+    // and the first of their edges
+    let mut starts = Vec::new();
+    for &(x, y) in region.cells.iter() {
+        lazy.prepare(data, cell_grid, x, y, opt)?;
+        for edge in START_EDGES.iter() {
+            if cell_grid[x][y]
+                .as_ref()
+                .is_some_and(|cell| cell.edges.contains_key(edge))
+            {
+                let n_removed = lazy.removed.len();
+                trace_ring(
+                    data,
+                    cell_grid,
+                    (x, y),
+                    edge,
+                    opt,
+                    &mut walked_frame,
+                    Some(&mut lazy),
+                )?;
+                starts.push(
+                    lazy.removed[n_removed..]
+                        .iter()
+                        .map(|(x, y, enter, _)| {
+                            (*x, *y, START_EDGES.iter().position(|e| e == enter).unwrap())
+                        })
+                        .min()
+                        .unwrap(),
+                );
+            }
+        }
+    }
+
+    // Then trace them again from these edges, in the order of a full computation
+    lazy.restore_edges(cell_grid);
+    starts.sort_unstable();
+    let mut rings = Vec::new();
+    for (x, y, edge) in starts {
+        if !cell_grid[x][y]
+            .as_ref()
+            .is_some_and(|cell| cell.edges.contains_key(&START_EDGES[edge]))
+        {
+            continue;
+        }
+        rings.push(trace_ring(
+            data,
+            cell_grid,
+            (x, y),
+            &START_EDGES[edge],
+            opt,
+            &mut walked_frame,
+            Some(&mut lazy),
+        )?);
+    }
+    lazy.clear(cell_grid);
+
+    // The unchanged rings are kept as is (the exterior rings and the interior rings
+    // staying in the order in which they were sorted), and the other ones are replaced
+    let Band {
+        geometry,
+        min_v,
+        max_v,
+    } = previous;
+    let mut kept = geometry
+        .0
+        .into_iter()
+        .rev()
+        .flat_map(|polygon| {
+            let (exterior, interiors) = polygon.into_inner();
+            std::iter::once(exterior).chain(interiors)
+        })
+        .filter(|ring| !region.contains(ring))
+        .map(|ring| {
+            let ring_area = area(&ring.0);
+            (ring, ring_area)
+        })
+        .collect::<Vec<_>>();
+    kept.extend(
+        builder
+            .transform_rings(rings)
+            .into_iter()
+            .filter(|(ring, _)| region.contains(ring)),
+    );
+    builder.rings_to_multipolygon(kept, min_v, max_v)
+}
@@ -1,12 +1,24 @@
 use crate::contains::contains;
+use crate::coverage::{_cell_coverage, _zonal_stats, BandCoverage, ZonalStats};
 use crate::errors::{new_error, Error, ErrorKind, Result};
-use crate::grid::BorrowedGrid;
-use crate::polygons::trace_band_paths;
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::incremental::{update_band, DirtyRegion};
+use crate::isolines::{_isolines, Isoline};
+use crate::polygons::{require_frame, trace_band_paths};
+use crate::prepared::PreparedGrid;
 use crate::quadtree::QuadTree;
 use crate::shape_coordinates::prepare_cell;
-use crate::utils::{empty_cell_grid, is_winding_correct};
-use geo_types::{LineString, MultiPolygon, Point, Polygon};
+use crate::simplify::simplify_bands;
+use crate::smooth::{smooth_bands, Smoothing};
+use crate::streaming::StripContourer;
+use crate::thresholds::{_thresholds, Classification};
+use crate::utils::{
+    empty_cell_grid, interpolate_bilinear, interpolate_coordinate, is_strictly_monotonic,
+    is_winding_correct,
+};
+use geo_types::{LineString, MultiLineString, MultiPolygon, Point, Polygon, Rect};
 use rustc_hash::FxHashMap;
+use std::borrow::Cow;
 
 use crate::area::area;
 #[cfg(feature = "parallel")]
@@ -35,11 +47,77 @@ pub struct Band {
     pub geometry: MultiPolygon<f64>,
 }
 
+/// Geometric statistics of an isoband (see [`Band::stats`]),
+/// expressed in the output coordinates of the grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandStats {
+    /// The number of polygons
+    pub polygons: usize,
+    /// The number of holes (interior rings) of the polygons
+    pub holes: usize,
+    /// The area of the polygons (minus the area of their holes)
+    pub area: f64,
+    /// The length of all the rings (exterior and interior rings)
+    pub perimeter: f64,
+    /// The bounding box of the polygons (`None` if the band is empty)
+    pub bbox: Option<Rect<f64>>,
+    /// The number of vertices of all the rings (without counting their closing point twice)
+    pub vertices: usize,
+}
+
 impl Band {
     pub fn geometry(&self) -> &MultiPolygon<f64> {
         &self.geometry
     }
 
+    /// Computes geometric statistics of the isoband
+    /// (number of polygons and holes, area, perimeter, bounding box and number of vertices).
+    pub fn stats(&self) -> BandStats {
+        let mut stats = BandStats {
+            polygons: self.geometry.0.len(),
+            holes: 0,
+            area: 0.,
+            perimeter: 0.,
+            bbox: None,
+            vertices: 0,
+        };
+
+        for polygon in self.geometry.0.iter() {
+            stats.holes += polygon.interiors().len();
+            // The area function skips the division by 2 of the shoelace formula
+            stats.area += (area(&polygon.exterior().0).abs()
+                - polygon
+                    .interiors()
+                    .iter()
+                    .map(|ring| area(&ring.0).abs())
+                    .sum::<f64>())
+                / 2.;
+            for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors().iter()) {
+                stats.perimeter += ring
+                    .0
+                    .windows(2)
+                    .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+                    .sum::<f64>();
+                stats.vertices += if ring.is_closed() {
+                    ring.0.len().saturating_sub(1)
+                } else {
+                    ring.0.len()
+                };
+            }
+            for coord in polygon.exterior().0.iter() {
+                stats.bbox = Some(match stats.bbox {
+                    None => Rect::new(*coord, *coord),
+                    Some(bbox) => Rect::new(
+                        (bbox.min().x.min(coord.x), bbox.min().y.min(coord.y)),
+                        (bbox.max().x.max(coord.x), bbox.max().y.max(coord.y)),
+                    ),
+                });
+            }
+        }
+
+        stats
+    }
+
     pub fn into_inner(self) -> (MultiPolygon<f64>, f64, f64) {
         (self.geometry, self.min_v, self.max_v)
     }
@@ -69,6 +147,43 @@ impl Band {
             foreign_members: None,
         }
     }
+
+    #[cfg(feature = "wkt")]
+    /// Convert the geometry of the isoband to WKT (as a MULTIPOLYGON)
+    ///
+    /// Coordinates are written with the shortest representation
+    /// that is read back as the same `f64` value.
+    pub fn to_wkt(&self) -> String {
+        crate::wkt::write_multipolygon(&self.geometry)
+    }
+
+    #[cfg(feature = "wkt")]
+    /// Read an isoband from its geometry in WKT (MULTIPOLYGON or POLYGON)
+    /// and its minimum and maximum values.
+    pub fn from_wkt(wkt: &str, min_v: f64, max_v: f64) -> Result<Band> {
+        Ok(Band {
+            geometry: crate::wkt::read_multipolygon(wkt)?,
+            min_v,
+            max_v,
+        })
+    }
+
+    #[cfg(feature = "wkb")]
+    /// Convert the geometry of the isoband to WKB (as a MultiPolygon, in little endian byte order)
+    pub fn to_wkb(&self) -> Vec<u8> {
+        crate::wkb::write_multipolygon(&self.geometry)
+    }
+
+    #[cfg(feature = "wkb")]
+    /// Read an isoband from its geometry in WKB (MultiPolygon or Polygon)
+    /// and its minimum and maximum values.
+    pub fn from_wkb(wkb: &[u8], min_v: f64, max_v: f64) -> Result<Band> {
+        Ok(Band {
+            geometry: crate::wkb::read_multipolygon(wkb)?,
+            min_v,
+            max_v,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +220,9 @@ pub(crate) struct MoveInfo {
 #[derive(Debug)]
 pub(crate) struct Edge {
     pub path: [Pt; 2],
+    /// A point between the start and the end of the path, if it isn't a straight line
+    /// (see [`Interpolation::Pixelated`])
+    pub via: Option<Pt>,
     pub move_info: MoveInfo,
 }
 
@@ -112,9 +230,182 @@ pub(crate) struct Edge {
 pub(crate) struct Settings {
     pub min_v: f64,
     pub max_v: f64,
+    pub nodata_boundary: NoDataBoundary,
+    pub saddle_resolution: SaddleResolution,
+    pub interpolation: Interpolation,
+}
+
+/// Where the boundary of the bands is placed next to missing values
+/// (NaN, no-data value or masked values).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoDataBoundary {
+    /// The cells having missing corners are dropped, i.e. the bands
+    /// stop on the last valid cells of the grid.
+    #[default]
+    DropCell,
+    /// The bands are cut half-way between the last valid points of the grid
+    /// and the missing ones, so that they hug the footprint of the valid data.
+    HalfCell,
 }
 
-static PRECISION: f64 = 1e-13;
+/// How the saddle cells (whose opposite corners are on the same side of a threshold,
+/// while their adjacent corners are not) are resolved, i.e. whether their corners
+/// with the highest values are connected through their center.
+///
+/// The center of the cell is given a single value, classified against each band,
+/// so that adjacent bands are always resolved consistently (without gaps nor overlaps).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaddleResolution {
+    /// The value of the center is the average value of the four corners
+    /// (as in GDAL and matplotlib).
+    #[default]
+    CenterAverage,
+    /// The value of the center is the value of the saddle point of the bilinear
+    /// interpolation of the four corners (the asymptotic decider).
+    AsymptoticDecider,
+    /// The value of the center is the highest value of the four corners, so that
+    /// the corners with the highest values are always connected (e.g. ridges stay connected).
+    Connect,
+    /// The value of the center is the lowest value of the four corners, so that
+    /// the corners with the highest values are always separated (e.g. valleys stay connected).
+    Separate,
+}
+
+impl SaddleResolution {
+    /// Classify the center of a saddle cell, given the values of its corners
+    /// (`x0`, `x1`, `x2` and `x3`), as below (0), inside (1) or above (2) the band.
+    pub(crate) fn center(&self, corners: [f64; 4], min_v: f64, max_v: f64) -> u8 {
+        // Missing values are not taken into account
+        let valid = corners.iter().copied().filter(|v| !v.is_nan());
+        let (sum, n) = valid
+            .clone()
+            .fold((0., 0.), |(sum, n), v| (sum + v, n + 1.));
+        let average = sum / n;
+
+        let value = match self {
+            SaddleResolution::CenterAverage => average,
+            SaddleResolution::AsymptoticDecider => {
+                let [x0, x1, x2, x3] = corners;
+                let denominator = x0 + x2 - x1 - x3;
+                if denominator == 0. || denominator.is_nan() {
+                    average
+                } else {
+                    (x0 * x2 - x1 * x3) / denominator
+                }
+            }
+            SaddleResolution::Connect => valid.fold(f64::NEG_INFINITY, f64::max),
+            SaddleResolution::Separate => valid.fold(f64::INFINITY, f64::min),
+        };
+
+        if value > max_v {
+            2
+        } else if value < min_v {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+/// How the position of the boundary of the bands (or of the isolines)
+/// is computed on the edges of the cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear interpolation between the values of the two ends of the edge.
+    #[default]
+    Linear,
+    /// Linear interpolation between the logarithms of the values of the two ends
+    /// of the edge, for values spanning several orders of magnitude (such as population
+    /// densities or concentrations); the linear interpolation is used when one of the values
+    /// is not strictly positive.
+    Logarithmic,
+    /// No interpolation: each point of the grid is the center of a pixel that belongs
+    /// entirely to one band, so that the boundaries follow the boundaries of the pixels
+    /// (half-way between the points of the grid), giving stair-stepped polygons.
+    Pixelated,
+}
+
+impl Interpolation {
+    /// The position (between 0 and 1) of `value` on an edge going
+    /// from the value `a` to the value `b`.
+    pub(crate) fn position(&self, a: f64, b: f64, value: f64) -> f64 {
+        match self {
+            Interpolation::Logarithmic if a > 0. && b > 0. && value > 0. => {
+                (value.ln() - a.ln()) / (b.ln() - a.ln())
+            }
+            Interpolation::Linear | Interpolation::Logarithmic => (value - a) / (b - a),
+            Interpolation::Pixelated => 0.5,
+        }
+    }
+}
+
+/// The default tolerance used to exclude the bound of the intervals
+/// that is not included in the bands (see [`IntervalClosure`]).
+pub(crate) static PRECISION: f64 = 1e-13;
+
+/// Which bound of the intervals defined by two consecutive thresholds
+/// is included in the bands.
+///
+/// The excluded bound is moved towards the inside of the band by a small tolerance
+/// (see [`ContourBuilder::interval_tolerance`]), so that the values equal
+/// to a threshold belong to a single band.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntervalClosure {
+    /// The bands are left-closed, `[min, max)`, except the last band
+    /// which also contains its maximum value, `[min, max]`.
+    #[default]
+    Left,
+    /// The bands are right-closed, `(min, max]`, except the first band
+    /// which also contains its minimum value, `[min, max]`.
+    Right,
+}
+
+/// Computes the settings of each band defined by the thresholds
+/// (with the minimum and maximum values of the band).
+pub(crate) fn band_settings(
+    thresholds: &[f64],
+    interval_closure: IntervalClosure,
+    interval_tolerance: f64,
+    nodata_boundary: NoDataBoundary,
+    saddle_resolution: SaddleResolution,
+    interpolation: Interpolation,
+) -> Result<Vec<(Settings, f64, f64)>> {
+    if thresholds.len() < 2 {
+        return Err(new_error(ErrorKind::BadIntervals));
+    }
+    Ok((0..thresholds.len() - 1)
+        .map(|band| {
+            let (min_v, max_v) =
+                band_limits(thresholds, band, interval_closure, interval_tolerance);
+            let opt = Settings {
+                min_v,
+                max_v,
+                nodata_boundary,
+                saddle_resolution,
+                interpolation,
+            };
+            (opt, thresholds[band], thresholds[band + 1])
+        })
+        .collect())
+}
+
+/// The minimum and maximum values of a band, the excluded bound
+/// being moved towards the inside of the band by `interval_tolerance`.
+pub(crate) fn band_limits(
+    thresholds: &[f64],
+    band: usize,
+    interval_closure: IntervalClosure,
+    interval_tolerance: f64,
+) -> (f64, f64) {
+    let (min_v, max_v) = (thresholds[band], thresholds[band + 1]);
+    match interval_closure {
+        IntervalClosure::Left if band + 2 < thresholds.len() => (min_v, max_v - interval_tolerance),
+        IntervalClosure::Right if band > 0 => (min_v + interval_tolerance, max_v),
+        _ => (min_v, max_v),
+    }
+}
+
+static IDENTITY_TRANSFORM: [f64; 6] = [0., 1., 0., 0., 0., 1.];
 
 /// Contours generator, using builder pattern, to
 /// be used on a rectangular `Slice` of values to
@@ -124,21 +415,43 @@ static PRECISION: f64 = 1e-13;
 /// [`isobands`]: fn.isobands.html
 pub struct ContourBuilder {
     /// The width of the grid
-    width: usize,
+    pub(crate) width: usize,
     /// The height of the grid
-    height: usize,
-    /// The horizontal coordinate for the origin of the grid.
-    x_origin: f64,
-    /// The vertical coordinate for the origin of the grid.
-    y_origin: f64,
-    /// The horizontal step for the grid
-    x_step: f64,
-    /// The vertical step for the grid
-    y_step: f64,
+    pub(crate) height: usize,
+    /// The affine transform from grid coordinates (column, row) to output coordinates:
+    /// x = transform[0] + column * transform[1] + row * transform[2] and
+    /// y = transform[3] + column * transform[4] + row * transform[5]
+    /// (`x_origin`, `x_step`, `y_origin` and `y_step` are respectively
+    /// stored in transform[0], transform[1], transform[3] and transform[5]).
+    transform: [f64; 6],
+    /// The coordinates of the columns of a rectilinear grid (replacing the affine transform on x)
+    x_coords: Option<Vec<f64>>,
+    /// The coordinates of the rows of a rectilinear grid (replacing the affine transform on y)
+    y_coords: Option<Vec<f64>>,
+    /// The coordinates (x and y) of each node of a curvilinear grid (replacing all the above)
+    curvilinear_coords: Option<(Vec<f64>, Vec<f64>)>,
     /// Whether to use a quadtree
-    use_quad_tree: bool,
+    pub(crate) use_quad_tree: bool,
     /// Winding order
-    ensure_rings_orientation: bool,
+    pub(crate) ensure_rings_orientation: bool,
+    /// The value that marks missing data
+    pub(crate) nodata: Option<f64>,
+    /// The validity mask (`false` marks missing data)
+    pub(crate) mask: Option<Vec<bool>>,
+    /// Where to place the boundary of the bands next to missing data
+    nodata_boundary: NoDataBoundary,
+    /// How the boundary of the bands is placed on the edges of the cells
+    pub(crate) interpolation: Interpolation,
+    /// How the saddle cells are resolved
+    pub(crate) saddle_resolution: SaddleResolution,
+    /// Which bound of the intervals is included in the bands
+    pub(crate) interval_closure: IntervalClosure,
+    /// The tolerance used to exclude the other bound of the intervals
+    pub(crate) interval_tolerance: f64,
+    /// The tolerance of the simplification of the bands (in grid cells)
+    simplify_tolerance: f64,
+    /// The smoothing of the bands (if any)
+    smoothing: Option<Smoothing>,
 }
 
 impl ContourBuilder {
@@ -151,36 +464,103 @@ impl ContourBuilder {
         ContourBuilder {
             width,
             height,
-            x_origin: 0f64,
-            y_origin: 0f64,
-            x_step: 1f64,
-            y_step: 1f64,
+            transform: IDENTITY_TRANSFORM,
+            x_coords: None,
+            y_coords: None,
+            curvilinear_coords: None,
             use_quad_tree: false,
             ensure_rings_orientation: true,
+            nodata: None,
+            mask: None,
+            nodata_boundary: NoDataBoundary::DropCell,
+            interpolation: Interpolation::Linear,
+            saddle_resolution: SaddleResolution::CenterAverage,
+            interval_closure: IntervalClosure::Left,
+            interval_tolerance: PRECISION,
+            simplify_tolerance: 0.,
+            smoothing: None,
         }
     }
 
     /// Sets the x origin of the grid.
     pub fn x_origin(mut self, x_origin: impl Into<f64>) -> Self {
-        self.x_origin = x_origin.into();
+        self.transform[0] = x_origin.into();
         self
     }
 
     /// Sets the y origin of the grid.
     pub fn y_origin(mut self, y_origin: impl Into<f64>) -> Self {
-        self.y_origin = y_origin.into();
+        self.transform[3] = y_origin.into();
         self
     }
 
     /// Sets the x step of the grid.
     pub fn x_step(mut self, x_step: impl Into<f64>) -> Self {
-        self.x_step = x_step.into();
+        self.transform[1] = x_step.into();
         self
     }
 
     /// Sets the y step of the grid.
     pub fn y_step(mut self, y_step: impl Into<f64>) -> Self {
-        self.y_step = y_step.into();
+        self.transform[5] = y_step.into();
+        self
+    }
+
+    /// Sets the full affine transform of the grid, given as a GDAL-style geotransform
+    /// (which allows rotated or sheared grids):
+    /// `[x_origin, pixel_width, row_rotation, y_origin, column_rotation, pixel_height]`.
+    ///
+    /// As in GDAL, the origin is the top-left corner of the top-left pixel,
+    /// while the values of the grid are located at the center of the pixels.
+    /// This replaces the values set by `x_origin`, `y_origin`, `x_step` and `y_step`.
+    pub fn geotransform(mut self, geotransform: [f64; 6]) -> Self {
+        let [x0, x_col, x_row, y0, y_col, y_row] = geotransform;
+        self.transform = [
+            x0 + 0.5 * x_col + 0.5 * x_row,
+            x_col,
+            x_row,
+            y0 + 0.5 * y_col + 0.5 * y_row,
+            y_col,
+            y_row,
+        ];
+        self
+    }
+
+    /// Sets the x coordinate of each column of the grid,
+    /// for rectilinear grids with a non-uniform spacing.
+    ///
+    /// The coordinates must be sorted (in ascending or descending order)
+    /// and their number must be equal to the width of the grid.
+    /// The vertices are placed by linear interpolation between the coordinates
+    /// of the two surrounding columns.
+    /// This replaces the x coordinates computed from `x_origin` and `x_step` (or `geotransform`).
+    pub fn x_coords(mut self, x_coords: &[f64]) -> Self {
+        self.x_coords = Some(x_coords.to_vec());
+        self
+    }
+
+    /// Sets the y coordinate of each row of the grid,
+    /// for rectilinear grids with a non-uniform spacing.
+    ///
+    /// The coordinates must be sorted (in ascending or descending order)
+    /// and their number must be equal to the height of the grid.
+    /// The vertices are placed by linear interpolation between the coordinates
+    /// of the two surrounding rows.
+    /// This replaces the y coordinates computed from `y_origin` and `y_step` (or `geotransform`).
+    pub fn y_coords(mut self, y_coords: &[f64]) -> Self {
+        self.y_coords = Some(y_coords.to_vec());
+        self
+    }
+
+    /// Sets the x and y coordinates of each node of the grid, for curvilinear grids
+    /// (such as the ones used by ocean or atmospheric models).
+    ///
+    /// Both slices are stored in row-major order, like the values of the grid,
+    /// and must have the same size as the grid.
+    /// The vertices are placed by bilinear interpolation between the four nodes of their cell.
+    /// This replaces the coordinates computed from all the other coordinate options.
+    pub fn curvilinear_coords(mut self, x_coords: &[f64], y_coords: &[f64]) -> Self {
+        self.curvilinear_coords = Some((x_coords.to_vec(), y_coords.to_vec()));
         self
     }
 
@@ -196,42 +576,180 @@ impl ContourBuilder {
         self
     }
 
+    /// Sets the value that marks missing data in the grid
+    /// (NaN values are always considered as missing). It is converted
+    /// to the type of the values of the grid before being compared to them.
+    pub fn nodata(mut self, nodata: impl Into<f64>) -> Self {
+        self.nodata = Some(nodata.into());
+        self
+    }
+
+    /// Sets the validity mask of the grid: it must have the same size as the grid,
+    /// and values whose mask is `false` are considered as missing.
+    pub fn mask(mut self, mask: &[bool]) -> Self {
+        self.mask = Some(mask.to_vec());
+        self
+    }
+
+    /// Sets where the boundary of the bands is placed next to missing data
+    /// (by default, the cells having missing corners are dropped).
+    pub fn nodata_boundary(mut self, nodata_boundary: NoDataBoundary) -> Self {
+        self.nodata_boundary = nodata_boundary;
+        self
+    }
+
+    /// Sets how the boundary of the bands (and the isolines) is placed on the edges
+    /// of the cells (by default, using a linear interpolation).
+    ///
+    /// With [`Interpolation::Pixelated`] and [`NoDataBoundary::HalfCell`], the bands
+    /// follow exactly the footprint of the pixels inside them (the missing values
+    /// being pixels outside of all the bands).
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Sets how the saddle cells are resolved (by default, using the average
+    /// value of their four corners), for both the bands and the isolines.
+    pub fn saddle_resolution(mut self, saddle_resolution: SaddleResolution) -> Self {
+        self.saddle_resolution = saddle_resolution;
+        self
+    }
+
+    /// Sets which bound of the intervals defined by two consecutive thresholds
+    /// is included in the bands (by default, the bands are left-closed,
+    /// except the last one which is closed).
+    ///
+    /// The first and last thresholds can be `f64::NEG_INFINITY` and `f64::INFINITY`
+    /// to get open-ended bands (e.g. all the values below the second threshold).
+    pub fn interval_closure(mut self, interval_closure: IntervalClosure) -> Self {
+        self.interval_closure = interval_closure;
+        self
+    }
+
+    /// Sets the tolerance used to exclude the bound of the intervals that is not
+    /// included in the bands (by default, `1e-13`): the excluded bound is moved
+    /// towards the inside of the band by this amount.
+    pub fn interval_tolerance(mut self, interval_tolerance: impl Into<f64>) -> Self {
+        self.interval_tolerance = interval_tolerance.into();
+        self
+    }
+
+    /// Computes the settings of each band defined by the thresholds.
+    pub(crate) fn band_settings(&self, thresholds: &[f64]) -> Result<Vec<(Settings, f64, f64)>> {
+        band_settings(
+            thresholds,
+            self.interval_closure,
+            self.interval_tolerance,
+            self.nodata_boundary,
+            self.saddle_resolution,
+            self.interpolation,
+        )
+    }
+
+    /// Sets the tolerance used to simplify the bands (by default, `0.`, i.e.
+    /// the bands are not simplified), expressed in cells of the grid
+    /// (i.e. before the coordinates of the grid are transformed).
+    ///
+    /// The boundaries shared by adjacent bands are simplified only once (using the Douglas-Peucker
+    /// algorithm), so that the bands stay without gaps nor overlaps between them,
+    /// and the simplification is reduced where it would make rings cross each other
+    /// or move a ring over another one (e.g. a hole outside of its exterior ring).
+    pub fn simplify(mut self, tolerance: impl Into<f64>) -> Self {
+        self.simplify_tolerance = tolerance.into();
+        self
+    }
+
+    /// Sets the smoothing of the bands (by default, the bands are not smoothed),
+    /// applied after the simplification (if any).
+    ///
+    /// The boundaries shared by adjacent bands are smoothed only once, so that the bands
+    /// stay without gaps nor overlaps between them, and the points where
+    /// several boundaries meet, as well as the points on the frame of the grid, are not moved.
+    pub fn smooth(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = Some(smoothing);
+        self
+    }
+
+    /// Simplifies and smooths the rings of the bands (if requested).
+    pub(crate) fn post_process(&self, bands: &mut [BandRaw]) {
+        simplify_bands(bands, self.simplify_tolerance);
+        if let Some(smoothing) = self.smoothing {
+            smooth_bands(bands, smoothing, self.width, self.height);
+        }
+    }
+
+    /// Wraps the given data in a grid, checking its dimensions
+    /// (and the dimensions of the mask and of the coordinates, if any).
+    pub(crate) fn grid<'a, T: GridValue>(&'a self, data: &'a [T]) -> Result<BorrowedGrid<'a, T>> {
+        check_dimensions(data.len(), self.width, self.height)?;
+        self.check_options()?;
+
+        Ok(BorrowedGrid::new(data, self.width, self.height)
+            .with_nodata(self.nodata)
+            .with_mask(self.mask.as_deref()))
+    }
+
+    /// Checks that the dimensions of the mask and of the coordinates (if any)
+    /// match the dimensions of the grid, and that the coordinates of the
+    /// rectilinear grids are sorted.
+    pub(crate) fn check_options(&self) -> Result<()> {
+        let len = self.width * self.height;
+        if let Some(mask) = &self.mask {
+            if mask.len() != len {
+                return Err(new_error(ErrorKind::BadDimension));
+            }
+        }
+        if let Some(x_coords) = &self.x_coords {
+            if x_coords.len() != self.width {
+                return Err(new_error(ErrorKind::BadDimension));
+            }
+            if !is_strictly_monotonic(x_coords) {
+                return Err(new_error(ErrorKind::BadCoordinates));
+            }
+        }
+        if let Some(y_coords) = &self.y_coords {
+            if y_coords.len() != self.height {
+                return Err(new_error(ErrorKind::BadDimension));
+            }
+            if !is_strictly_monotonic(y_coords) {
+                return Err(new_error(ErrorKind::BadCoordinates));
+            }
+        }
+        if let Some((x_coords, y_coords)) = &self.curvilinear_coords {
+            if x_coords.len() != len || y_coords.len() != len {
+                return Err(new_error(ErrorKind::BadDimension));
+            }
+        }
+        Ok(())
+    }
+
     /// Generates contour MultiPolygons for the given data and thresholds.
-    pub fn contours(&self, data: &[f64], thresholds: &[f64]) -> Result<Vec<Band>> {
+    pub fn contours<T: GridValue>(&self, data: &[T], thresholds: &[f64]) -> Result<Vec<Band>> {
         // Generate the paths for each threshold (returned as a Vec of BandRaw)
-        let bands = isobands(
-            data,
-            thresholds,
+        let mut bands = _isobands(
+            self.grid(data)?,
+            &self.band_settings(thresholds)?,
             self.use_quad_tree,
-            self.width,
-            self.height,
             false,
         )?;
+        self.post_process(&mut bands);
 
         // Build a MultiPolygon for each band
-        // and returns a Vec of Band
-        let res = bands
-            .into_iter()
-            .map(|(raw_band, min_v, max_v)| {
-                self.convert_rings_to_multipolygon(raw_band, min_v, max_v)
-            })
-            .collect::<Result<Vec<Band>>>()?;
-
-        Ok(res)
+        self.bands_from_raw(bands)
     }
 
     /// Generates contour MultiPolygons for the given data and thresholds.
     #[cfg(feature = "parallel")]
-    pub fn par_contours(&self, data: &[f64], thresholds: &[f64]) -> Result<Vec<Band>> {
+    pub fn par_contours<T: GridValue>(&self, data: &[T], thresholds: &[f64]) -> Result<Vec<Band>> {
         // Generate the paths for each threshold (returned as a Vec of BandRaw)
-        let bands = isobands(
-            data,
-            thresholds,
+        let mut bands = _isobands(
+            self.grid(data)?,
+            &self.band_settings(thresholds)?,
             self.use_quad_tree,
-            self.width,
-            self.height,
             true,
         )?;
+        self.post_process(&mut bands);
 
         // Build a MultiPolygon for each band
         // and returns a Vec of Band
@@ -245,14 +763,429 @@ impl ContourBuilder {
         Ok(res)
     }
 
-    fn convert_rings_to_multipolygon(
+    /// Generates contour MultiPolygons for each grid of a sequence of grids
+    /// having the same dimensions (such as the frames of a forecast), with the same thresholds.
+    ///
+    /// The bands of each frame are in the order of the thresholds (one [`Band`] for each pair
+    /// of consecutive thresholds, even when it is empty), so that the frames can be compared
+    /// band by band. The cells allocated to trace the bands are reused for all the frames.
+    pub fn contours_series<T: GridValue, S: AsRef<[T]>>(
+        &self,
+        frames: &[S],
+        thresholds: &[f64],
+    ) -> Result<Vec<Vec<Band>>> {
+        let bands = self.band_settings(thresholds)?;
+        let mut cell_grid = Vec::new();
+        frames
+            .iter()
+            .map(|frame| self.frame_contours(frame.as_ref(), &bands, &mut cell_grid))
+            .collect()
+    }
+
+    /// Generates contour MultiPolygons for each grid of a sequence of grids
+    /// (see [`ContourBuilder::contours_series`]), computing the frames in parallel.
+    #[cfg(feature = "parallel")]
+    pub fn par_contours_series<T: GridValue, S: AsRef<[T]> + Sync>(
+        &self,
+        frames: &[S],
+        thresholds: &[f64],
+    ) -> Result<Vec<Vec<Band>>> {
+        let bands = self.band_settings(thresholds)?;
+        frames
+            .par_iter()
+            .map_init(Vec::new, |cell_grid, frame| {
+                self.frame_contours(frame.as_ref(), &bands, cell_grid)
+            })
+            .collect()
+    }
+
+    /// Generates contour MultiPolygons for one frame of a sequence of grids,
+    /// reusing the given cell grid (allocated on the first frame).
+    fn frame_contours<T: GridValue>(
+        &self,
+        data: &[T],
+        bands: &[(Settings, f64, f64)],
+        cell_grid: &mut Vec<Vec<Option<Cell>>>,
+    ) -> Result<Vec<Band>> {
+        let grid = self.grid(data)?;
+        let tree = if self.use_quad_tree {
+            Some(QuadTree::new(&grid))
+        } else {
+            None
+        };
+        if cell_grid.is_empty() {
+            *cell_grid = empty_cell_grid(self.width, self.height);
+        }
+
+        let mut bands = trace_bands(&grid, bands, tree.as_ref(), cell_grid)?;
+        self.post_process(&mut bands);
+        self.bands_from_raw(bands)
+    }
+
+    /// Computes thresholds for the given data, according to the given classification scheme
+    /// (the first threshold is the minimum value and the last one is the maximum value).
+    ///
+    /// Missing values (NaN, no-data value or masked values) are ignored.
+    pub fn thresholds<T: GridValue>(
+        &self,
+        data: &[T],
+        classification: Classification,
+    ) -> Result<Vec<f64>> {
+        let grid = self.grid(data)?;
+        _thresholds(
+            (0..self.height)
+                .flat_map(|j| (0..self.width).map(move |i| (i, j)))
+                .map(|p| grid.value(p)),
+            classification,
+        )
+    }
+
+    /// Computes, for each band defined by the given thresholds, the fraction
+    /// of each cell of the grid covered by the band (see [`BandCoverage`]).
+    ///
+    /// The fractions are computed exactly from the polygons drawn by the marching squares
+    /// in each cell, so that they are consistent with the geometry of the bands
+    /// (in grid coordinates, i.e. before the transformation of the coordinates).
+    pub fn cell_coverage<T: GridValue>(
+        &self,
+        data: &[T],
+        thresholds: &[f64],
+    ) -> Result<Vec<BandCoverage>> {
+        _cell_coverage(&self.grid(data)?, &self.band_settings(thresholds)?)
+    }
+
+    /// Computes statistics (weighted count, sum, mean, minimum and maximum)
+    /// of the values of a secondary grid, co-registered with the grid of `data`,
+    /// inside each band defined by the given thresholds.
+    ///
+    /// Each value is weighted by the fraction of its pixel covered by the band
+    /// (computed exactly from the polygons of the band, see [`ZonalStats`]).
+    /// NaN values of the secondary grid are ignored.
+    pub fn zonal_stats<T: GridValue, U: GridValue>(
+        &self,
+        data: &[T],
+        values: &[U],
+        thresholds: &[f64],
+    ) -> Result<Vec<ZonalStats>> {
+        let grid = self.grid(data)?;
+        check_dimensions(values.len(), self.width, self.height)?;
+        _zonal_stats(&grid, values, &self.band_settings(thresholds)?)
+    }
+
+    /// Prepares the given data (borrowed or owned) for computing the contours
+    /// several times with different thresholds (see [`PreparedGrid`]).
+    pub fn prepare<'a, T: GridValue>(
+        &'a self,
+        data: impl Into<Cow<'a, [T]>>,
+    ) -> Result<PreparedGrid<'a, T>> {
+        PreparedGrid::new(self, data.into())
+    }
+
+    /// Updates the bands computed by [`ContourBuilder::contours`] with the given thresholds
+    /// after a change of the values of the points `(x, y)` of the grid such that
+    /// `min_x <= x < max_x` and `min_y <= y < max_y` (`dirty` being `[min_x, min_y, max_x, max_y]`
+    /// and `data` the whole updated grid).
+    ///
+    /// Only the cells around the changed values are prepared again: the rings going through
+    /// them are traced again and replace the previous rings around these values, the other
+    /// rings being kept as is, so that the result is the same as the one of
+    /// [`ContourBuilder::contours`]. All the bands are computed again when `previous` is empty
+    /// or when the bands are simplified or smoothed (their shared boundaries depending on
+    /// all the bands), and so is a band whose frame may be modified (i.e. when the change is
+    /// on the frame of the grid, or when the frame is inside the band).
+    ///
+    /// Returns an error if the bands of `previous` aren't the ones of the thresholds.
+    pub fn update_isobands<T: GridValue>(
+        &self,
+        previous: Vec<Band>,
+        data: &[T],
+        thresholds: &[f64],
+        dirty: [usize; 4],
+    ) -> Result<Vec<Band>> {
+        let grid = self.grid(data)?;
+        if dirty[2] > self.width || dirty[3] > self.height {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let bands = self.band_settings(thresholds)?;
+        if previous.is_empty()
+            || self.simplify_tolerance > 0.
+            || self.smoothing.is_some()
+            || self.width < 2
+            || self.height < 2
+        {
+            return self.contours(data, thresholds);
+        }
+        if previous.len() != bands.len()
+            || previous
+                .iter()
+                .zip(bands.iter())
+                .any(|(band, (_, min_v, max_v))| band.min_v != *min_v || band.max_v != *max_v)
+        {
+            return Err(new_error(ErrorKind::BadIntervals));
+        }
+        if dirty[0] >= dirty[2] || dirty[1] >= dirty[3] {
+            return Ok(previous);
+        }
+
+        let on_frame =
+            dirty[0] == 0 || dirty[1] == 0 || dirty[2] == self.width || dirty[3] == self.height;
+        let region = DirtyRegion::new(self, dirty);
+        let mut cell_grid = empty_cell_grid(self.width, self.height);
+        previous
+            .into_iter()
+            .zip(bands.iter())
+            .map(|(band, settings)| {
+                // Whether the frame of the grid is a ring of the band depends on all the values
+                // on the frame, and on all the rings going along it
+                if on_frame || require_frame(&grid, &settings.0) {
+                    let (rings, min_v, max_v) =
+                        trace_bands(&grid, std::slice::from_ref(settings), None, &mut cell_grid)?
+                            .remove(0);
+                    cell_grid
+                        .iter_mut()
+                        .for_each(|row| row.iter_mut().for_each(|cell| *cell = None));
+                    self.convert_rings_to_multipolygon(rings, min_v, max_v)
+                } else {
+                    update_band(self, &grid, &mut cell_grid, &region, &settings.0, band)
+                }
+            })
+            .collect()
+    }
+
+    /// Creates a streaming contours generator for the given thresholds,
+    /// to which the grid is given by strips of rows
+    /// (see [`StripContourer`] for more details).
+    pub fn strip_contourer(&self, thresholds: &[f64]) -> Result<StripContourer<'_>> {
+        StripContourer::new(self, thresholds)
+    }
+
+    /// Generates contour MultiPolygons for the given thresholds,
+    /// reading the grid by strips of rows (from an iterator of slices
+    /// whose lengths are multiples of the width of the grid).
+    ///
+    /// Only the rings (and not the whole grid) are kept in memory,
+    /// see [`StripContourer`] to get the rings as soon as they are closed.
+    pub fn contours_from_strips<T, S, I>(&self, strips: I, thresholds: &[f64]) -> Result<Vec<Band>>
+    where
+        T: GridValue,
+        S: AsRef<[T]>,
+        I: IntoIterator<Item = S>,
+    {
+        let bands = self.isobands_from_strips(strips, thresholds)?;
+        self.bands_from_raw(bands)
+    }
+
+    /// Computes the (post-processed) rings of the bands for the given thresholds,
+    /// reading the grid by strips of rows.
+    pub(crate) fn isobands_from_strips<T, S, I>(
+        &self,
+        strips: I,
+        thresholds: &[f64],
+    ) -> Result<Vec<BandRaw>>
+    where
+        T: GridValue,
+        S: AsRef<[T]>,
+        I: IntoIterator<Item = S>,
+    {
+        let contourer = self.strip_contourer(thresholds)?;
+        let raw_bands = thresholds
+            .windows(2)
+            .map(|pair| (Vec::new(), pair[0], pair[1]))
+            .collect();
+        self.read_strips(contourer, raw_bands, strips)
+    }
+
+    /// Polygonizes a categorical grid (such as a land cover or a zoning raster),
+    /// returning one [`Band`] for each distinct value of the grid (in increasing order,
+    /// with this value as minimum and maximum value), covering the pixels equal to this value.
+    ///
+    /// Each point of the grid is the center of a pixel (as with [`Interpolation::Pixelated`]),
+    /// so that the boundaries follow the boundaries of the pixels and are exactly shared
+    /// by the adjacent classes. Missing values are outside of all the classes (with
+    /// [`NoDataBoundary::HalfCell`], the classes follow exactly the footprint of their pixels).
+    pub fn classes<T: GridValue>(&self, data: &[T]) -> Result<Vec<Band>> {
+        let grid = self.grid(data)?;
+        let mut classes = (0..self.height)
+            .flat_map(|j| (0..self.width).map(move |i| (i, j)))
+            .map(|p| grid.value(p))
+            .filter(|v| !v.is_nan())
+            .collect::<Vec<f64>>();
+        classes.sort_unstable_by(f64::total_cmp);
+        classes.dedup();
+
+        let bands = classes
+            .iter()
+            .map(|&class| {
+                let opt = Settings {
+                    min_v: class,
+                    max_v: class,
+                    nodata_boundary: self.nodata_boundary,
+                    saddle_resolution: self.saddle_resolution,
+                    interpolation: Interpolation::Pixelated,
+                };
+                (opt, class, class)
+            })
+            .collect::<Vec<_>>();
+        let mut bands = _isobands(grid, &bands, self.use_quad_tree, false)?;
+        self.post_process(&mut bands);
+        self.bands_from_raw(bands)
+    }
+
+    /// Reads the grid by strips with the given contourer,
+    /// adding the rings to the (empty) bands before post-processing them.
+    fn read_strips<T, S, I>(
+        &self,
+        mut contourer: StripContourer<'_>,
+        mut raw_bands: Vec<BandRaw>,
+        strips: I,
+    ) -> Result<Vec<BandRaw>>
+    where
+        T: GridValue,
+        S: AsRef<[T]>,
+        I: IntoIterator<Item = S>,
+    {
+        for strip in strips {
+            for (band, ring) in contourer.push_strip_raw(strip.as_ref())? {
+                raw_bands[band].0.push(ring);
+            }
+        }
+        for (band, ring) in contourer.finish_raw()? {
+            raw_bands[band].0.push(ring);
+        }
+        self.post_process(&mut raw_bands);
+        Ok(raw_bands)
+    }
+
+    /// Builds the MultiPolygon of each band from its rings (in grid coordinates).
+    pub(crate) fn bands_from_raw(&self, bands: Vec<BandRaw>) -> Result<Vec<Band>> {
+        bands
+            .into_iter()
+            .map(|(raw_band, min_v, max_v)| {
+                self.convert_rings_to_multipolygon(raw_band, min_v, max_v)
+            })
+            .collect::<Result<Vec<Band>>>()
+    }
+
+    /// Generates isolines (as MultiLineStrings) for the given data and thresholds.
+    ///
+    /// One [`Isoline`] is returned for each threshold value: lines reaching the
+    /// border of the grid are left open while the other ones are closed rings.
+    pub fn isolines<T: GridValue>(&self, data: &[T], thresholds: &[f64]) -> Result<Vec<Isoline>> {
+        // Generate the lines for each threshold (returned as a Vec of IsolineRaw)
+        let lines = _isolines(
+            self.grid(data)?,
+            thresholds,
+            self.use_quad_tree,
+            self.saddle_resolution,
+            self.interpolation,
+        );
+
+        let res = lines
+            .into_iter()
+            .map(|(raw_lines, value)| {
+                let geometry = raw_lines
+                    .into_iter()
+                    .map(|mut points| {
+                        points.dedup();
+                        self.transform_points(&mut points);
+                        LineString::from(points)
+                    })
+                    .filter(|line| line.0.len() > 1)
+                    .collect::<MultiLineString<f64>>();
+                Isoline { value, geometry }
+            })
+            .collect::<Vec<Isoline>>();
+
+        Ok(res)
+    }
+
+    /// Whether the coordinates of the points are transformed
+    /// (i.e. are not the column and row of the grid).
+    pub(crate) fn is_transformed(&self) -> bool {
+        self.transform != IDENTITY_TRANSFORM
+            || self.x_coords.is_some()
+            || self.y_coords.is_some()
+            || self.curvilinear_coords.is_some()
+    }
+
+    /// Use the affine transform of the grid (and the coordinates of the columns / rows
+    /// for rectilinear grids, or of the nodes for curvilinear grids)
+    /// to calculate the coordinates of the points if they are not the default ones
+    pub(crate) fn transform_points(&self, points: &mut [Point<f64>]) {
+        if !self.is_transformed() {
+            return;
+        }
+        if let Some((x_coords, y_coords)) = &self.curvilinear_coords {
+            points.iter_mut().for_each(|point| {
+                let (col, row) = point.x_y();
+                *point = Point::new(
+                    interpolate_bilinear(x_coords, self.width, self.height, col, row),
+                    interpolate_bilinear(y_coords, self.width, self.height, col, row),
+                );
+            });
+        } else {
+            let [x0, x_col, x_row, y0, y_col, y_row] = self.transform;
+            points.iter_mut().for_each(|point| {
+                let (col, row) = point.x_y();
+                let x = match &self.x_coords {
+                    Some(x_coords) => interpolate_coordinate(x_coords, col),
+                    None => x0 + col * x_col + row * x_row,
+                };
+                let y = match &self.y_coords {
+                    Some(y_coords) => interpolate_coordinate(y_coords, row),
+                    None => y0 + col * y_col + row * y_row,
+                };
+                *point = Point::new(x, y);
+            });
+        }
+    }
+
+    /// Computes the extent (`[min_x, min_y, max_x, max_y]`) of the grid, in output coordinates,
+    /// and whether the y axis is pointing up (i.e. if the y coordinate decreases along the rows).
+    pub(crate) fn grid_extent(&self) -> ([f64; 4], bool) {
+        let (w, h) = (self.width.max(1), self.height.max(1));
+        let mut points = (0..w)
+            .flat_map(|i| [(i, 0), (i, h - 1)])
+            .chain((0..h).flat_map(|j| [(0, j), (w - 1, j)]))
+            .map(|(i, j)| Point::new(i as f64, j as f64))
+            .collect::<Vec<_>>();
+        self.transform_points(&mut points);
+
+        let mut extent = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for point in points.iter() {
+            extent[0] = extent[0].min(point.x());
+            extent[1] = extent[1].min(point.y());
+            extent[2] = extent[2].max(point.x());
+            extent[3] = extent[3].max(point.y());
+        }
+        // The first point is the first node of the first row,
+        // and the second one the first node of the last row
+        let y_up = points[1].y() < points[0].y();
+        (extent, y_up)
+    }
+
+    pub(crate) fn convert_rings_to_multipolygon(
         &self,
         raw_band: Vec<Vec<Point<f64>>>,
         min_v: f64,
         max_v: f64,
     ) -> Result<Band> {
+        self.rings_to_multipolygon(self.transform_rings(raw_band), min_v, max_v)
+    }
+
+    /// Converts the rings of a band (in grid coordinates) to LineStrings
+    /// in output coordinates, with their (signed) area.
+    pub(crate) fn transform_rings(
+        &self,
+        raw_band: Vec<Vec<Point<f64>>>,
+    ) -> Vec<(LineString<f64>, f64)> {
         // First, convert the isobands paths to LineStrings
-        let mut rings: Vec<(LineString<f64>, f64)> = raw_band
+        raw_band
             .into_iter()
             .map(|mut points| {
                 // Sometimes paths have repeated points, so we remove them first
@@ -262,18 +1195,7 @@ impl ContourBuilder {
             // We dont want 'empty' rings
             .filter(|potential_ring| potential_ring.len() > 2)
             .map(|mut points| {
-                // Use x_origin, y_origin, x_step and y_step to calculate the coordinates of the points
-                // if they are not the default values
-                if (self.x_origin, self.y_origin) != (0f64, 0f64)
-                    || (self.x_step, self.y_step) != (1f64, 1f64)
-                {
-                    points.iter_mut().for_each(|point| {
-                        let pt_x = point.x_mut();
-                        *pt_x = self.x_origin + *pt_x * self.x_step;
-                        let pt_y = point.y_mut();
-                        *pt_y = self.y_origin + *pt_y * self.y_step;
-                    });
-                }
+                self.transform_points(&mut points);
 
                 // We also compute the area now as we will need it to sort the rings
                 // (+ also later to check if a ring is clockwise or not)
@@ -281,12 +1203,30 @@ impl ContourBuilder {
                 let area = area(&closed_linestring.0);
                 (closed_linestring, area)
             })
-            .collect::<Vec<(LineString<f64>, f64)>>();
+            // Nor rings without area (they can appear next to missing data)
+            .filter(|(_, area)| *area != 0.)
+            .collect::<Vec<(LineString<f64>, f64)>>()
+    }
 
+    /// Builds the MultiPolygon of a band from its rings (in output coordinates, with their area).
+    pub(crate) fn rings_to_multipolygon(
+        &self,
+        mut rings: Vec<(LineString<f64>, f64)>,
+        min_v: f64,
+        max_v: f64,
+    ) -> Result<Band> {
         // We sort by absolute area, so that the smallest rings are first
         // (this will help later when we reconstruct the polygons by checking which rings are enclosed by others
         // in for rings enclosed by more than one other ring, we will keep the smallest one)
-        rings.sort_by_key(|(_, area)| area.abs() as u64);
+        // (areas are compared as floats since they can be very small, e.g. with geographic coordinates)
+        // The rings with the same area are sorted by their first point, so that their order
+        // doesn't depend on the order in which they were traced (see `update_isobands`)
+        rings.sort_by(|(ring_a, a), (ring_b, b)| {
+            a.abs().total_cmp(&b.abs()).then_with(|| {
+                let (a, b) = (ring_a.0[0], ring_b.0[0]);
+                a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+            })
+        });
 
         // Then we compute how many times a ring is enclosed by another ring
         let mut enclosed_by_n = FxHashMap::default();
@@ -370,218 +1310,158 @@ impl ContourBuilder {
 /// squares algorithm that contains the paths of the Band as a Vec of Vec
 /// of Points - this is the intermediate result that is used to build
 /// the MultiPolygons in the [`ContourBuilder::contours`] method).
-pub fn isobands(
-    data: &[f64],
+pub fn isobands<T: GridValue>(
+    data: &[T],
     thresholds: &[f64],
     use_quad_tree: bool,
     width: usize,
     height: usize,
     _parallel: bool,
 ) -> Result<Vec<BandRaw>> {
-    if data.is_empty() {
+    check_dimensions(data.len(), width, height)?;
+
+    let data = BorrowedGrid::new(data, width, height);
+
+    let bands = band_settings(
+        thresholds,
+        IntervalClosure::default(),
+        PRECISION,
+        NoDataBoundary::default(),
+        SaddleResolution::default(),
+        Interpolation::default(),
+    )?;
+    _isobands(data, &bands, use_quad_tree, _parallel)
+}
+
+pub(crate) fn check_dimensions(len: usize, width: usize, height: usize) -> Result<()> {
+    if len == 0 {
         return Err(new_error(ErrorKind::BadData));
     }
-    if data.len() != width * height {
+    if len != width * height {
         return Err(new_error(ErrorKind::BadDimension));
     }
-    if thresholds.len() < 2 {
-        return Err(new_error(ErrorKind::BadIntervals));
-    }
-
-    let data = BorrowedGrid::new(data, width, height);
+    Ok(())
+}
 
+pub(crate) fn _isobands<T: GridValue>(
+    data: BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+    use_quad_tree: bool,
+    _parallel: bool,
+) -> Result<Vec<BandRaw>> {
     #[cfg(feature = "parallel")]
     if _parallel {
         if use_quad_tree {
-            _isobands_parallel_quadtree_raw(data, thresholds)
+            _isobands_parallel_quadtree_raw(data, bands)
         } else {
-            _isobands_parallel_raw(data, thresholds)
+            _isobands_parallel_raw(data, bands)
         }
     } else {
         if use_quad_tree {
-            _isobands_quadtree_raw(data, thresholds)
+            _isobands_quadtree_raw(data, bands)
         } else {
-            _isobands_raw(data, thresholds)
+            _isobands_raw(data, bands)
         }
     }
     #[cfg(not(feature = "parallel"))]
     if use_quad_tree {
-        _isobands_quadtree_raw(data, thresholds)
+        _isobands_quadtree_raw(data, bands)
     } else {
-        _isobands_raw(data, thresholds)
+        _isobands_raw(data, bands)
     }
 }
 
-fn _isobands_raw(data: BorrowedGrid<f64>, thresholds: &[f64]) -> Result<Vec<BandRaw>> {
-    let lj = data.height();
-    let li = data.width();
-    let n_pair_thresholds = thresholds.len() - 1;
-
+fn _isobands_raw<T: GridValue>(
+    data: BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+) -> Result<Vec<BandRaw>> {
     // Allocate the cell grid once
-    let mut cell_grid: Vec<Vec<Option<Cell>>> = empty_cell_grid(li, lj);
-
-    let res = thresholds
-        .iter()
-        .zip(thresholds.iter().skip(1))
-        .enumerate()
-        .map(|(i, (&min, &max))| -> Result<BandRaw> {
-            // Store min / max values for the current band
-            let opt = Settings {
-                min_v: min,
-                max_v: if i + 1 == n_pair_thresholds {
-                    max
-                } else {
-                    max - PRECISION
-                },
-            };
-
-            // Fill up the grid with cell information
-            cell_grid.iter_mut().enumerate().try_for_each(|(i, row)| {
-                row.iter_mut().enumerate().try_for_each(|(j, cell)| {
-                    *cell = prepare_cell(i, j, &data, &opt)?;
-                    Ok::<(), Error>(())
-                })
-            })?;
-
-            let band_polygons = trace_band_paths(&data, &mut cell_grid, &opt)?;
-            Ok((band_polygons, min, max))
-        })
-        .collect::<Result<Vec<BandRaw>>>()?;
-
-    Ok(res)
+    let mut cell_grid = empty_cell_grid(data.width(), data.height());
+    trace_bands(&data, bands, None, &mut cell_grid)
 }
 
 #[cfg(feature = "parallel")]
-fn _isobands_parallel_raw(data: BorrowedGrid<f64>, thresholds: &[f64]) -> Result<Vec<BandRaw>> {
-    let lj = data.height();
-    let li = data.width();
-    let n_pair_thresholds = thresholds.len() - 1;
-
-    let res = thresholds
-        .iter()
-        .zip(thresholds.iter().skip(1))
-        .enumerate()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(|(i, (&min, &max))| -> Result<BandRaw> {
-            // Store min / max values for the current band
-            let opt = Settings {
-                min_v: min,
-                max_v: if i + 1 == n_pair_thresholds {
-                    max
-                } else {
-                    max - PRECISION
-                },
-            };
-
-            let mut cell_grid: Vec<Vec<Option<Cell>>> = empty_cell_grid(li, lj);
-
-            // Fill up the grid with cell information
-            cell_grid.iter_mut().enumerate().try_for_each(|(i, row)| {
-                row.iter_mut().enumerate().try_for_each(|(j, cell)| {
-                    *cell = prepare_cell(i, j, &data, &opt)?;
-                    Ok::<(), Error>(())
-                })
-            })?;
-
-            let band_polygons = trace_band_paths(&data, &mut cell_grid, &opt)?;
-            Ok((band_polygons, min, max))
+fn _isobands_parallel_raw<T: GridValue>(
+    data: BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+) -> Result<Vec<BandRaw>> {
+    bands
+        .par_iter()
+        .map(|band| -> Result<BandRaw> {
+            let mut cell_grid = empty_cell_grid(data.width(), data.height());
+            Ok(trace_bands(&data, std::slice::from_ref(band), None, &mut cell_grid)?.remove(0))
         })
-        .collect::<Result<Vec<BandRaw>>>()?;
-
-    Ok(res)
+        .collect::<Result<Vec<BandRaw>>>()
 }
 
-fn _isobands_quadtree_raw(data: BorrowedGrid<f64>, thresholds: &[f64]) -> Result<Vec<BandRaw>> {
-    let lj = data.height();
-    let li = data.width();
-    let n_pair_thresholds = thresholds.len() - 1;
-
+fn _isobands_quadtree_raw<T: GridValue>(
+    data: BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+) -> Result<Vec<BandRaw>> {
     // Instantiate the quadtree
     let tree = QuadTree::new(&data);
 
     // Allocate the cell grid once
-    let mut cell_grid: Vec<Vec<Option<Cell>>> = empty_cell_grid(li, lj);
-
-    let res = thresholds
-        .iter()
-        .zip(thresholds.iter().skip(1))
-        .enumerate()
-        .map(|(i, (&min, &max))| -> Result<BandRaw> {
-            // Store min / max values for the current band
-            let opt = Settings {
-                min_v: min,
-                max_v: if i + 1 == n_pair_thresholds {
-                    max
-                } else {
-                    max - PRECISION
-                },
-            };
-
-            // Clear the grid
-            if i > 0 {
-                cell_grid.iter_mut().for_each(|row| {
-                    row.iter_mut().for_each(|cell| {
-                        *cell = None;
-                    })
-                });
-            }
-
-            // Fill up the grid with cell information
-            for (i, j) in tree.cells_in_band(opt.min_v, opt.max_v) {
-                cell_grid[i][j] = prepare_cell(i, j, &data, &opt)?;
-            }
-
-            let band_polygons = trace_band_paths(&data, &mut cell_grid, &opt)?;
-
-            Ok((band_polygons, min, max))
-        })
-        .collect::<Result<Vec<BandRaw>>>()?;
-
-    Ok(res)
+    let mut cell_grid = empty_cell_grid(data.width(), data.height());
+    trace_bands(&data, bands, Some(&tree), &mut cell_grid)
 }
 
 #[cfg(feature = "parallel")]
-fn _isobands_parallel_quadtree_raw(
-    data: BorrowedGrid<f64>,
-    thresholds: &[f64],
+fn _isobands_parallel_quadtree_raw<T: GridValue>(
+    data: BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
 ) -> Result<Vec<BandRaw>> {
-    let lj = data.height();
-    let li = data.width();
-    let n_pair_thresholds = thresholds.len() - 1;
-
     // Instantiate the quadtree
     let tree = QuadTree::new(&data);
 
-    let res = thresholds
-        .iter()
-        .zip(thresholds.iter().skip(1))
-        .enumerate()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(|(i, (&min, &max))| -> Result<BandRaw> {
-            // Store min / max values for the current band
-            let opt = Settings {
-                min_v: min,
-                max_v: if i + 1 == n_pair_thresholds {
-                    max
-                } else {
-                    max - PRECISION
-                },
-            };
-
-            let mut cell_grid: Vec<Vec<Option<Cell>>> = empty_cell_grid(li, lj);
+    bands
+        .par_iter()
+        .map(|band| -> Result<BandRaw> {
+            let mut cell_grid = empty_cell_grid(data.width(), data.height());
+            Ok(trace_bands(
+                &data,
+                std::slice::from_ref(band),
+                Some(&tree),
+                &mut cell_grid,
+            )?
+            .remove(0))
+        })
+        .collect::<Result<Vec<BandRaw>>>()
+}
 
+/// Traces the rings of each band, reusing the given cell grid
+/// (and only visiting the cells found by the quadtree, if any).
+pub(crate) fn trace_bands<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    bands: &[(Settings, f64, f64)],
+    tree: Option<&QuadTree>,
+    cell_grid: &mut [Vec<Option<Cell>>],
+) -> Result<Vec<BandRaw>> {
+    bands
+        .iter()
+        .map(|(opt, min, max)| -> Result<BandRaw> {
             // Fill up the grid with cell information
-            for (i, j) in tree.cells_in_band(opt.min_v, opt.max_v) {
-                cell_grid[i][j] = prepare_cell(i, j, &data, &opt)?;
+            match tree {
+                Some(tree) => {
+                    cell_grid
+                        .iter_mut()
+                        .for_each(|row| row.iter_mut().for_each(|cell| *cell = None));
+                    for (i, j) in tree.cells_in_band(opt.min_v, opt.max_v) {
+                        cell_grid[i][j] = prepare_cell(i, j, data, opt)?;
+                    }
+                }
+                None => {
+                    cell_grid.iter_mut().enumerate().try_for_each(|(i, row)| {
+                        row.iter_mut().enumerate().try_for_each(|(j, cell)| {
+                            *cell = prepare_cell(i, j, data, opt)?;
+                            Ok::<(), Error>(())
+                        })
+                    })?;
+                }
             }
 
-            let band_polygons = trace_band_paths(&data, &mut cell_grid, &opt)?;
-
-            Ok((band_polygons, min, max))
+            let band_polygons = trace_band_paths(data, cell_grid, opt)?;
+            Ok((band_polygons, *min, *max))
         })
-        .collect::<Result<Vec<BandRaw>>>()?;
-
-    Ok(res)
+        .collect::<Result<Vec<BandRaw>>>()
 }
@@ -0,0 +1,318 @@
+use crate::errors::Result;
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::isobands::{check_dimensions, Interpolation, SaddleResolution};
+use crate::quadtree::QuadTree;
+use geo_types::{MultiLineString, Point};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// The raw result of the isoline computation,
+/// where the first element is a vector of lines
+/// and the second is the threshold value.
+/// See the [`Isoline`] struct for a more convenient representation.
+pub type IsolineRaw = (Vec<Vec<Point<f64>>>, f64);
+
+/// An isoline, described by its threshold value and MultiLineString.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Isoline {
+    /// The threshold value of the isoline
+    pub value: f64,
+    /// The MultiLineString following the points equal to value
+    pub geometry: MultiLineString<f64>,
+}
+
+impl Isoline {
+    pub fn geometry(&self) -> &MultiLineString<f64> {
+        &self.geometry
+    }
+
+    pub fn into_inner(self) -> (MultiLineString<f64>, f64) {
+        (self.geometry, self.value)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    #[cfg(feature = "geojson")]
+    /// Convert the isoline to a GeoJSON Feature
+    ///
+    /// To get a string representation, call to_geojson().to_string().
+    pub fn to_geojson(&self) -> geojson::Feature {
+        let mut properties = geojson::JsonObject::with_capacity(1);
+        properties.insert("value".to_string(), self.value.into());
+
+        geojson::Feature {
+            bbox: None,
+            geometry: Some(geojson::Geometry::from(self.geometry())),
+            id: None,
+            properties: Some(properties),
+            foreign_members: None,
+        }
+    }
+}
+
+/// The identifier of a cell edge, expressed on a grid of doubled resolution
+/// (so that an edge shared by two cells has the same key in both cells):
+/// horizontal edges have an odd x and an even y,
+/// vertical edges have an even x and an odd y.
+type EdgeKey = (usize, usize);
+
+#[derive(Clone, Copy)]
+enum Side {
+    B,
+    R,
+    T,
+    L,
+}
+
+#[inline]
+fn edge_key(x: usize, y: usize, side: Side) -> EdgeKey {
+    match side {
+        Side::B => (2 * x + 1, 2 * y),
+        Side::R => (2 * x + 2, 2 * y + 1),
+        Side::T => (2 * x + 1, 2 * y + 2),
+        Side::L => (2 * x, 2 * y + 1),
+    }
+}
+
+/// Compute the position of the crossing point of the isoline on a given edge.
+/// The interpolation always starts from the corner with the smallest index,
+/// so that the point is the same for the two cells sharing this edge.
+fn edge_point<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    key: EdgeKey,
+    threshold: f64,
+    interpolation: Interpolation,
+) -> Point<f64> {
+    let (x, y) = (key.0 / 2, key.1 / 2);
+    let a = data.value((x, y));
+    if key.0 % 2 == 1 {
+        let b = data.value((x + 1, y));
+        Point::new(x as f64 + interpolation.position(a, b, threshold), y as f64)
+    } else {
+        let b = data.value((x, y + 1));
+        Point::new(x as f64, y as f64 + interpolation.position(a, b, threshold))
+    }
+}
+
+/// Get the points of a line crossing the given edges.
+/// With [`Interpolation::Pixelated`], the line follows the boundaries of the pixels,
+/// so it turns at the center of the cells where it crosses two perpendicular edges.
+fn line_points<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    keys: &[EdgeKey],
+    threshold: f64,
+    interpolation: Interpolation,
+) -> Vec<Point<f64>> {
+    let mut points = Vec::with_capacity(keys.len());
+    for (i, &key) in keys.iter().enumerate() {
+        if interpolation == Interpolation::Pixelated && i > 0 {
+            let previous = keys[i - 1];
+            if previous.0 % 2 != key.0 % 2 {
+                let (horizontal, vertical) = if key.0 % 2 == 1 {
+                    (key, previous)
+                } else {
+                    (previous, key)
+                };
+                points.push(Point::new(horizontal.0 as f64 / 2., vertical.1 as f64 / 2.));
+            }
+        }
+        points.push(edge_point(data, key, threshold, interpolation));
+    }
+    points
+}
+
+/// Oriented segments for each of the 16 cases of the marching squares algorithm
+/// (bits are set, from the least significant one, for the bottom-left,
+/// bottom-right, top-right and top-left corners that are above the threshold).
+/// Segments are oriented so that the values above the threshold are on their left.
+/// Saddle cases (5 and 10) are resolved separately.
+const CASES: [&[(Side, Side)]; 16] = [
+    &[],
+    &[(Side::B, Side::L)],
+    &[(Side::R, Side::B)],
+    &[(Side::R, Side::L)],
+    &[(Side::T, Side::R)],
+    &[],
+    &[(Side::T, Side::B)],
+    &[(Side::T, Side::L)],
+    &[(Side::L, Side::T)],
+    &[(Side::B, Side::T)],
+    &[],
+    &[(Side::R, Side::T)],
+    &[(Side::L, Side::R)],
+    &[(Side::B, Side::R)],
+    &[(Side::L, Side::B)],
+    &[],
+];
+
+fn cell_segments<T: GridValue>(
+    x: usize,
+    y: usize,
+    data: &BorrowedGrid<T>,
+    threshold: f64,
+    saddle_resolution: SaddleResolution,
+    interpolation: Interpolation,
+    segments: &mut Vec<(EdgeKey, EdgeKey)>,
+) {
+    let x0 = data.value((x, y));
+    let x1 = data.value((x + 1, y));
+    let x2 = data.value((x + 1, y + 1));
+    let x3 = data.value((x, y + 1));
+
+    if x0.is_nan() || x1.is_nan() || x2.is_nan() || x3.is_nan() {
+        return;
+    }
+
+    let cval = (x0 >= threshold) as usize
+        | ((x1 >= threshold) as usize) << 1
+        | ((x2 >= threshold) as usize) << 2
+        | ((x3 >= threshold) as usize) << 3;
+
+    // Saddle points are disambiguated like for the isobands
+    // (i.e. as for the band made of the values above the threshold,
+    // whose pixels only touching by a corner are kept separate)
+    let center_above = interpolation != Interpolation::Pixelated
+        && saddle_resolution.center([x0, x1, x2, x3], threshold, f64::INFINITY) >= 1;
+
+    let sides: &[(Side, Side)] = match (cval, center_above) {
+        (5, true) => &[(Side::T, Side::L), (Side::B, Side::R)],
+        (5, false) => &[(Side::B, Side::L), (Side::T, Side::R)],
+        (10, true) => &[(Side::L, Side::B), (Side::R, Side::T)],
+        (10, false) => &[(Side::R, Side::B), (Side::L, Side::T)],
+        _ => CASES[cval],
+    };
+
+    for (start, end) in sides {
+        segments.push((edge_key(x, y, *start), edge_key(x, y, *end)));
+    }
+}
+
+/// Join the oriented segments into lines.
+/// Lines that are not closed start and end on the border of the grid
+/// (or next to a cell that contains NaN values).
+fn join_segments(segments: &[(EdgeKey, EdgeKey)]) -> Vec<Vec<EdgeKey>> {
+    let mut next: FxHashMap<EdgeKey, EdgeKey> = FxHashMap::default();
+    let mut has_previous: FxHashSet<EdgeKey> = FxHashSet::default();
+    for (start, end) in segments {
+        next.insert(*start, *end);
+        has_previous.insert(*end);
+    }
+
+    let mut lines = Vec::new();
+
+    let mut follow = |start: EdgeKey, next: &mut FxHashMap<EdgeKey, EdgeKey>| {
+        let mut line = vec![start];
+        let mut current = start;
+        while let Some(following) = next.remove(&current) {
+            line.push(following);
+            current = following;
+        }
+        lines.push(line);
+    };
+
+    // Open lines first, starting from the points that have no predecessor
+    for (start, _) in segments {
+        if !has_previous.contains(start) && next.contains_key(start) {
+            follow(*start, &mut next);
+        }
+    }
+
+    // Everything that remains is a closed ring
+    for (start, _) in segments {
+        if next.contains_key(start) {
+            follow(*start, &mut next);
+        }
+    }
+
+    lines
+}
+
+/// Generates isolines for the given data and thresholds.
+/// Returns a `Vec` of [`IsolineRaw`] (this is the raw result of the marching
+/// squares algorithm that contains the lines for each threshold as a Vec of Vec
+/// of Points - this is the intermediate result that is used to build
+/// the MultiLineStrings in the [`ContourBuilder::isolines`] method).
+///
+/// [`ContourBuilder::isolines`]: crate::ContourBuilder::isolines
+pub fn isolines<T: GridValue>(
+    data: &[T],
+    thresholds: &[f64],
+    use_quad_tree: bool,
+    width: usize,
+    height: usize,
+) -> Result<Vec<IsolineRaw>> {
+    check_dimensions(data.len(), width, height)?;
+
+    Ok(_isolines(
+        BorrowedGrid::new(data, width, height),
+        thresholds,
+        use_quad_tree,
+        SaddleResolution::default(),
+        Interpolation::default(),
+    ))
+}
+
+pub(crate) fn _isolines<T: GridValue>(
+    data: BorrowedGrid<T>,
+    thresholds: &[f64],
+    use_quad_tree: bool,
+    saddle_resolution: SaddleResolution,
+    interpolation: Interpolation,
+) -> Vec<IsolineRaw> {
+    let (width, height) = (data.width(), data.height());
+
+    let tree = if use_quad_tree && width > 1 && height > 1 {
+        Some(QuadTree::new(&data))
+    } else {
+        None
+    };
+
+    let mut segments = Vec::new();
+
+    thresholds
+        .iter()
+        .map(|&threshold| {
+            segments.clear();
+
+            if let Some(tree) = &tree {
+                // Visit the cells in the same order as without the quadtree
+                // so that the lines start at the same point in both cases
+                let mut cells = tree.cells_in_band(threshold, threshold);
+                cells.sort_unstable_by_key(|&(x, y)| (y, x));
+                for (x, y) in cells {
+                    cell_segments(
+                        x,
+                        y,
+                        &data,
+                        threshold,
+                        saddle_resolution,
+                        interpolation,
+                        &mut segments,
+                    );
+                }
+            } else {
+                for y in 0..height.saturating_sub(1) {
+                    for x in 0..width.saturating_sub(1) {
+                        cell_segments(
+                            x,
+                            y,
+                            &data,
+                            threshold,
+                            saddle_resolution,
+                            interpolation,
+                            &mut segments,
+                        );
+                    }
+                }
+            }
+
+            let lines = join_segments(&segments)
+                .into_iter()
+                .map(|keys| line_points(&data, &keys, threshold, interpolation))
+                .collect::<Vec<_>>();
+
+            (lines, threshold)
+        })
+        .collect::<Vec<IsolineRaw>>()
+}
@@ -50,20 +50,61 @@
 #![cfg_attr(debug_assertions, allow(dead_code))]
 mod area;
 mod contains;
+mod coverage;
 mod errors;
 mod grid;
+mod incremental;
 mod isobands;
+mod isolines;
+#[cfg(feature = "mvt")]
+mod mvt;
 mod polygons;
+mod prepared;
 mod quadtree;
 mod shape_coordinates;
+mod simplify;
+mod smooth;
+mod streaming;
+#[cfg(feature = "svg")]
+mod svg;
+mod thresholds;
+#[cfg(feature = "topojson")]
+mod topojson;
+mod topology;
 mod utils;
+#[cfg(feature = "wkb")]
+mod wkb;
+#[cfg(feature = "wkt")]
+mod wkt;
 
-pub use crate::isobands::{isobands, Band, BandRaw, ContourBuilder};
+pub use crate::coverage::{BandCoverage, ZonalStats};
+pub use crate::grid::GridValue;
+pub use crate::isobands::{
+    isobands, Band, BandRaw, BandStats, ContourBuilder, Interpolation, IntervalClosure,
+    NoDataBoundary, SaddleResolution,
+};
+pub use crate::isolines::{isolines, Isoline, IsolineRaw};
+#[cfg(feature = "mvt")]
+pub use crate::mvt::{MvtCoordinates, MvtTile};
+pub use crate::prepared::PreparedGrid;
+pub use crate::smooth::Smoothing;
+pub use crate::streaming::{BandRing, StripContourer};
+#[cfg(feature = "svg")]
+pub use crate::svg::{ColorRamp, SvgRenderer};
+pub use crate::thresholds::{nice_thresholds, thresholds, Classification};
+#[cfg(feature = "topojson")]
+pub use crate::topojson::TopoJsonWriter;
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
-    use crate::isobands::isobands;
-    use geo_types::Point;
+    use crate::isobands::{
+        isobands, Band, ContourBuilder, Interpolation, IntervalClosure, NoDataBoundary,
+        SaddleResolution,
+    };
+    use crate::isolines::isolines;
+    use crate::smooth::Smoothing;
+    use geo_types::{LineString, MultiPolygon, Point, Polygon};
 
     fn make_grid_from2d_vec(data: &[Vec<f64>]) -> (Vec<f64>, usize, usize) {
         let width = data[0].len();
@@ -75,6 +116,44 @@ mod tests {
         (grid, width, height)
     }
 
+    /// A 7x6 grid with a peak (15) in a plateau (5), around a pit (1).
+    fn peak_grid() -> (Vec<f64>, usize, usize) {
+        let matrix = vec![
+            vec![1., 1., 1., 1., 1., 1., 1.],
+            vec![1., 5., 5., 5., 5., 5., 1.],
+            vec![1., 5., 15., 15., 15., 5., 1.],
+            vec![1., 5., 1., 1., 1., 5., 1.],
+            vec![1., 5., 5., 5., 5., 5., 1.],
+            vec![1., 1., 1., 1., 1., 1., 1.],
+        ];
+        make_grid_from2d_vec(&matrix)
+    }
+
+    /// The values of a JSON fixture (with `data`, `width` and `height` fields),
+    /// with the width and height of the grid.
+    fn grid_from_json(json: &str) -> (Vec<f64>, usize, usize) {
+        let raw_data: serde_json::Value = serde_json::from_str(json).unwrap();
+        let matrix: Vec<f64> = raw_data["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x.as_f64().unwrap())
+            .collect();
+        let h = raw_data["height"].as_u64().unwrap() as usize;
+        let w = raw_data["width"].as_u64().unwrap() as usize;
+        (matrix, w, h)
+    }
+
+    /// The values of the volcano dataset, with the width and height of the grid.
+    fn volcano() -> (Vec<f64>, usize, usize) {
+        grid_from_json(include_str!("../tests/fixtures/volcano.json"))
+    }
+
+    /// The values of the pot_pop_fr dataset, with the width and height of the grid.
+    fn pot_pop() -> (Vec<f64>, usize, usize) {
+        grid_from_json(include_str!("../tests/fixtures/pot_pop_fr.json"))
+    }
+
     #[test]
     fn isobands_err_matrix_empty() {
         let matrix: Vec<Vec<f64>> = vec![vec![]];
@@ -193,6 +272,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn isobands_pentagon_and_saddle_cells() {
+        // A pentagon cell (2100): the right edge is crossed between x1 and x2
+        // (it used to be interpolated between x1 and x3, giving (1, 1/3))
+        let matrix = vec![vec![0., 0.], vec![3., 1.5]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let res = isobands(&matrix, &[1., 2.], false, width, height, false).unwrap();
+        assert_eq!(
+            res[0].0,
+            vec![vec![
+                Point::new(0., 2. / 3.),
+                Point::new(2. / 3., 1.),
+                Point::new(1., 1.),
+                Point::new(1., 2. / 3.),
+                Point::new(0., 1. / 3.),
+                Point::new(0., 2. / 3.),
+            ]]
+        );
+
+        // A saddle cell (0212) whose center is above the band: two polygons
+        // (the center average used to be ignored, giving a single heptagon)
+        let matrix = vec![vec![3., 1.5], vec![0.9, 3.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let res = isobands(&matrix, &[1., 2.], false, width, height, false).unwrap();
+        assert_eq!(
+            res[0].0,
+            vec![
+                vec![
+                    Point::new(0., 2. / 2.1),
+                    Point::new((1. - 0.9) / 2.1, 1.),
+                    Point::new(1.1 / 2.1, 1.),
+                    Point::new(0., 1. / 2.1),
+                    Point::new(0., 2. / 2.1),
+                ],
+                vec![
+                    Point::new(2. / 3., 0.),
+                    Point::new(1., 1. / 3.),
+                    Point::new(1., 0.),
+                    Point::new(2. / 3., 0.),
+                ],
+            ]
+        );
+    }
+
     #[test]
     fn isobands_example() {
         let matrix = vec![
@@ -620,4 +743,1544 @@ mod tests {
 
         assert_eq!(res1, res2);
     }
+
+    #[test]
+    fn isolines_open_line() {
+        let matrix = vec![vec![0., 1.], vec![0., 1.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+
+        let res = isolines(&matrix, &[0.5], false, width, height).unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0].0,
+            vec![vec![Point::new(0.5, 1.), Point::new(0.5, 0.)]]
+        );
+        assert_eq!(res[0].1, 0.5);
+    }
+
+    #[test]
+    fn isolines_closed_ring() {
+        let matrix = vec![vec![0., 0., 0.], vec![0., 1., 0.], vec![0., 0., 0.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+
+        let res = isolines(&matrix, &[0.5], false, width, height).unwrap();
+        assert_eq!(
+            res[0].0,
+            vec![vec![
+                Point::new(0.5, 1.),
+                Point::new(1., 0.5),
+                Point::new(1.5, 1.),
+                Point::new(1., 1.5),
+                Point::new(0.5, 1.),
+            ]]
+        );
+    }
+
+    #[test]
+    fn isolines_contour_builder_transform() {
+        let matrix = vec![0., 1., 0., 1.];
+
+        let res = ContourBuilder::new(2, 2)
+            .x_origin(10.)
+            .y_origin(20.)
+            .x_step(2.)
+            .y_step(-2.)
+            .isolines(&matrix, &[0.5, 2.])
+            .unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].value(), 0.5);
+        assert_eq!(
+            res[0].geometry().0,
+            vec![LineString::from(vec![(11., 18.), (11., 20.)])]
+        );
+        assert!(res[1].geometry().0.is_empty());
+    }
+
+    #[test]
+    /// Test that isolines returns the same result when using a quadtree or not (volcano dataset)
+    fn isolines_volcano_same_with_quadtree() {
+        let (matrix, w, h) = volcano();
+
+        let thresholds = [
+            90., 95., 100., 105., 110., 115., 120., 125., 130., 135., 140., 145., 150., 155., 160.,
+            165., 170., 175., 180., 185., 190., 195., 200.,
+        ];
+
+        let res1 = isolines(&matrix, &thresholds, false, w, h).unwrap();
+        let res2 = isolines(&matrix, &thresholds, true, w, h).unwrap();
+
+        assert_eq!(res1, res2);
+        // Each line is either closed or ends on the border of the grid
+        for (lines, _) in res1.iter() {
+            for line in lines {
+                let first = line[0];
+                let last = line[line.len() - 1];
+                let on_border = |p: Point<f64>| {
+                    p.x() == 0. || p.y() == 0. || p.x() == (w - 1) as f64 || p.y() == (h - 1) as f64
+                };
+                assert!(first == last || (on_border(first) && on_border(last)));
+            }
+        }
+    }
+
+    #[test]
+    /// Test that isobands returns the same result with integer or f32 values
+    /// as with the same values widened to f64
+    fn isobands_generic_values() {
+        let matrix_i16: Vec<i16> = vec![
+            1, 1, 1, 1, 1, 1, 1, 1, 5, 5, 5, 5, 5, 1, 1, 5, 15, 15, 15, 5, 1, 1, 5, 10, 10, 10, 5,
+            1, 1, 5, 5, 5, 5, 5, 1, 1, 1, 1, 1, 1, 1, 1,
+        ];
+        let matrix_f32: Vec<f32> = matrix_i16.iter().map(|&v| v as f32).collect();
+        let matrix_u8: Vec<u8> = matrix_i16.iter().map(|&v| v as u8).collect();
+        let matrix_f64: Vec<f64> = matrix_i16.iter().map(|&v| v as f64).collect();
+
+        let intervals = [3., 5., 7.];
+
+        let expected = isobands(&matrix_f64, &intervals, false, 7, 6, false).unwrap();
+        assert_eq!(
+            isobands(&matrix_i16, &intervals, false, 7, 6, false).unwrap(),
+            expected
+        );
+        assert_eq!(
+            isobands(&matrix_f32, &intervals, true, 7, 6, false).unwrap(),
+            expected
+        );
+        assert_eq!(
+            isobands(&matrix_u8, &intervals, true, 7, 6, false).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    /// Test that the no-data value, the mask and NaN values give the same result
+    /// (with and without quadtree)
+    fn isobands_nodata_mask_nan() {
+        let nodata = -9999.;
+        let matrix = vec![
+            vec![1., 1., 1., 1., 1., 1.],
+            vec![1., 5., 5., 5., 5., 1.],
+            vec![1., 5., 5., 5., 5., 1.],
+            vec![1., 5., 5., nodata, 5., 1.],
+            vec![1., 1., 1., 1., 1., nodata],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let matrix_nan: Vec<f64> = matrix
+            .iter()
+            .map(|&v| if v == nodata { f64::NAN } else { v })
+            .collect();
+        let mask: Vec<bool> = matrix.iter().map(|&v| v != nodata).collect();
+        let intervals = [0., 3., 6.];
+
+        for nodata_boundary in [NoDataBoundary::DropCell, NoDataBoundary::HalfCell] {
+            let builder = ContourBuilder::new(width, height).nodata_boundary(nodata_boundary);
+            let res_nodata = builder
+                .nodata(nodata)
+                .contours(&matrix, &intervals)
+                .unwrap();
+            let builder = ContourBuilder::new(width, height).nodata_boundary(nodata_boundary);
+            let res_mask = builder
+                .use_quad_tree(true)
+                .mask(&mask)
+                .contours(&matrix, &intervals)
+                .unwrap();
+            let builder = ContourBuilder::new(width, height).nodata_boundary(nodata_boundary);
+            let res_nan = builder.contours(&matrix_nan, &intervals).unwrap();
+
+            for ((a, b), c) in res_nodata.iter().zip(res_mask.iter()).zip(res_nan.iter()) {
+                assert_eq!(a.geometry(), b.geometry());
+                assert_eq!(a.geometry(), c.geometry());
+            }
+        }
+    }
+
+    #[test]
+    fn isobands_nodata_hole_half_cell() {
+        let matrix: Vec<i16> = vec![
+            5, 5, 5, 5, 5, //
+            5, 5, 5, 5, 5, //
+            5, 5, -9999, 5, 5, //
+            5, 5, 5, 5, 5, //
+            5, 5, 5, 5, 5,
+        ];
+
+        let res = ContourBuilder::new(5, 5)
+            .nodata(-9999)
+            .nodata_boundary(NoDataBoundary::HalfCell)
+            .contours(&matrix, &[0., 10.])
+            .unwrap();
+
+        assert_eq!(
+            res[0].geometry(),
+            &MultiPolygon::new(vec![Polygon::new(
+                LineString::from(vec![(0., 0.), (0., 4.), (4., 4.), (4., 0.), (0., 0.)]),
+                vec![LineString::from(vec![
+                    (1.5, 2.),
+                    (2., 1.5),
+                    (2.5, 2.),
+                    (2., 2.5),
+                    (1.5, 2.)
+                ])],
+            )])
+        );
+    }
+
+    #[test]
+    /// Test that the cells having a missing corner are entirely dropped
+    fn isobands_nodata_drop_cell() {
+        let mut matrix = vec![5.; 16];
+        matrix[15] = f64::NAN;
+
+        let res = ContourBuilder::new(4, 4)
+            .contours(&matrix, &[0., 10.])
+            .unwrap();
+        assert_eq!(
+            res[0].geometry(),
+            &MultiPolygon::new(vec![Polygon::new(
+                LineString::from(vec![(0., 0.), (0., 3.), (3., 3.), (3., 0.), (0., 0.)]),
+                vec![],
+            )])
+        );
+
+        let matrix = vec![
+            vec![1., 1., 1., 1., 1.],
+            vec![1., 5., 5., 5., 1.],
+            vec![1., 5., 5., 5., 1.],
+            vec![1., 5., 5., f64::NAN, 1.],
+            vec![1., 1., 1., 1., 1.],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let res = ContourBuilder::new(width, height)
+            .contours(&matrix, &[3., 10.])
+            .unwrap();
+        assert_eq!(
+            res[0].geometry(),
+            &MultiPolygon::new(vec![Polygon::new(
+                LineString::from(vec![
+                    (1., 0.5),
+                    (0.5, 1.),
+                    (0.5, 2.),
+                    (0.5, 3.),
+                    (1., 3.5),
+                    (2., 3.5),
+                    (2., 3.),
+                    (2., 2.),
+                    (3., 2.),
+                    (3.5, 2.),
+                    (3.5, 1.),
+                    (3., 0.5),
+                    (2., 0.5),
+                    (1., 0.5)
+                ]),
+                vec![],
+            )])
+        );
+    }
+
+    #[test]
+    fn isobands_nodata_f32() {
+        let nodata = -9999.9;
+        let matrix: Vec<f32> = vec![
+            1., 1., 1., 1., 1., //
+            1., 5., 5., 5., 1., //
+            1., 5., nodata, 5., 1., //
+            1., 5., 5., 5., 1., //
+            1., 1., 1., 1., 1.,
+        ];
+        let matrix_nan: Vec<f32> = matrix
+            .iter()
+            .map(|&v| if v == nodata { f32::NAN } else { v })
+            .collect();
+        let intervals = [0., 3., 6.];
+
+        let res_nodata = ContourBuilder::new(5, 5)
+            .nodata(-9999.9)
+            .contours(&matrix, &intervals)
+            .unwrap();
+        let res_nan = ContourBuilder::new(5, 5)
+            .contours(&matrix_nan, &intervals)
+            .unwrap();
+        for (a, b) in res_nodata.iter().zip(res_nan.iter()) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+    }
+
+    #[test]
+    fn isobands_err_mask_bad_dimension() {
+        let res = ContourBuilder::new(2, 2)
+            .mask(&[true, true, false])
+            .contours(&[1., 1., 1., 5.], &[0., 3.]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn contours_err_unsorted_coords() {
+        let matrix = [1., 1., 1., 1., 5., 1., 1., 1., 1.];
+        let res = ContourBuilder::new(3, 3)
+            .x_coords(&[0., 3., 1.])
+            .contours(&matrix, &[3., 10.]);
+        assert!(res.is_err());
+        let res = ContourBuilder::new(3, 3)
+            .y_coords(&[10., 10., 40.])
+            .contours(&matrix, &[3., 10.]);
+        assert!(res.is_err());
+        // Descending coordinates are allowed
+        let res = ContourBuilder::new(3, 3)
+            .y_coords(&[40., 20., 10.])
+            .contours(&matrix, &[3., 10.]);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn contours_rectilinear_grid() {
+        let res = ContourBuilder::new(3, 3)
+            .x_coords(&[0., 1., 3.])
+            .y_coords(&[10., 20., 40.])
+            .contours(&[1., 1., 1., 1., 5., 1., 1., 1., 1.], &[3., 10.])
+            .unwrap();
+        assert_eq!(
+            res[0].geometry(),
+            &MultiPolygon(vec![Polygon::new(
+                LineString::from(vec![(1., 15.), (0.5, 20.), (1., 30.), (2., 20.), (1., 15.),]),
+                vec![],
+            )])
+        );
+
+        let res = ContourBuilder::new(3, 3)
+            .x_coords(&[0., 1.])
+            .contours(&[1., 1., 1., 1., 5., 1., 1., 1., 1.], &[3., 10.]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn contours_curvilinear_grid() {
+        let (matrix, width, height) = peak_grid();
+        let intervals = [3., 7., 16.];
+
+        // A curvilinear grid whose nodes follow an affine transform
+        // gives the same result as the affine transform itself
+        let (mut x_coords, mut y_coords) = (vec![], vec![]);
+        for row in 0..height {
+            for col in 0..width {
+                x_coords.push(10. + 2. * col as f64 + 0.5 * row as f64);
+                y_coords.push(5. - 3. * row as f64);
+            }
+        }
+        let res = ContourBuilder::new(width, height)
+            .curvilinear_coords(&x_coords, &y_coords)
+            .contours(&matrix, &intervals)
+            .unwrap();
+        let expected = ContourBuilder::new(width, height)
+            .geotransform([8.75, 2., 0.5, 6.5, 0., -3.])
+            .contours(&matrix, &intervals)
+            .unwrap();
+        for (band, band_expected) in res.iter().zip(expected.iter()) {
+            let points = band.geometry().0.iter().flat_map(|p| {
+                p.exterior()
+                    .0
+                    .iter()
+                    .chain(p.interiors().iter().flat_map(|r| r.0.iter()))
+            });
+            let points_expected = band_expected.geometry().0.iter().flat_map(|p| {
+                p.exterior()
+                    .0
+                    .iter()
+                    .chain(p.interiors().iter().flat_map(|r| r.0.iter()))
+            });
+            assert_eq!(points.clone().count(), points_expected.clone().count());
+            for (pt, pt_expected) in points.zip(points_expected) {
+                assert!((pt.x - pt_expected.x).abs() < 1e-9);
+                assert!((pt.y - pt_expected.y).abs() < 1e-9);
+            }
+        }
+
+        // Vertices are mapped bilinearly in a non-linear mesh
+        let res = ContourBuilder::new(3, 3)
+            .curvilinear_coords(
+                &[0., 1., 4., 0., 1., 4., 0., 1., 4.],
+                &[0., 0., 0., 1., 2., 1., 2., 2., 2.],
+            )
+            .isolines(&[1., 1., 1., 1., 5., 1., 1., 1., 1.], &[3.])
+            .unwrap();
+        let mut points = res[0].geometry().0[0].0.clone();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        points.dedup();
+        assert_eq!(
+            points,
+            vec![
+                (0.5, 1.5).into(),
+                (1., 1.).into(),
+                (1., 2.).into(),
+                (2.5, 1.5).into()
+            ]
+        );
+
+        let res = ContourBuilder::new(width, height)
+            .curvilinear_coords(&x_coords[1..], &y_coords)
+            .contours(&matrix, &intervals);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    /// Test that rings orientation and holes are preserved with a rotated
+    /// (and flipped) geotransform
+    fn contours_rotated_geotransform() {
+        let (matrix, width, height) = peak_grid();
+        let intervals = [3., 7., 16.];
+
+        let reference = ContourBuilder::new(width, height)
+            .contours(&matrix, &intervals)
+            .unwrap();
+
+        let (sin, cos) = 0.5f64.sin_cos();
+        for geotransform in [
+            [100., 2. * cos, -2. * sin, 50., 2. * sin, 2. * cos],
+            [100., 2. * cos, 2. * sin, 50., 2. * sin, -2. * cos],
+        ] {
+            let res = ContourBuilder::new(width, height)
+                .geotransform(geotransform)
+                .contours(&matrix, &intervals)
+                .unwrap();
+
+            for (band, band_ref) in res.iter().zip(reference.iter()) {
+                assert_eq!(band.geometry().0.len(), band_ref.geometry().0.len());
+                for (polygon, polygon_ref) in
+                    band.geometry().0.iter().zip(band_ref.geometry().0.iter())
+                {
+                    assert!(crate::area::area(&polygon.exterior().0) > 0.);
+                    assert_eq!(polygon.interiors().len(), polygon_ref.interiors().len());
+                    for interior in polygon.interiors() {
+                        assert!(crate::area::area(&interior.0) < 0.);
+                    }
+                    // The first point is the center of the pixel in the middle of the ring
+                    let (col, row) = polygon_ref.exterior().0[0].x_y();
+                    let (col, row) = (col + 0.5, row + 0.5);
+                    let expected = (
+                        geotransform[0] + col * geotransform[1] + row * geotransform[2],
+                        geotransform[3] + col * geotransform[4] + row * geotransform[5],
+                    );
+                    let first = polygon.exterior().0[0].x_y();
+                    let last = polygon.exterior().0[polygon.exterior().0.len() - 1].x_y();
+                    assert!(
+                        (first.0 - expected.0).abs() < 1e-9 && (first.1 - expected.1).abs() < 1e-9
+                            || (last.0 - expected.0).abs() < 1e-9
+                                && (last.1 - expected.1).abs() < 1e-9
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Test that reading the grid by strips gives the same bands
+    /// as reading the whole grid (volcano dataset)
+    fn contours_from_strips_volcano() {
+        let (matrix, w, h) = volcano();
+
+        let thresholds = [
+            90., 95., 100., 105., 110., 115., 120., 125., 130., 135., 140., 145., 150., 155., 160.,
+            165., 170., 175., 180., 185., 190., 195., 200.,
+        ];
+
+        let builder = ContourBuilder::new(w, h);
+        let res1 = builder.contours(&matrix, &thresholds).unwrap();
+        let res2 = builder
+            .contours_from_strips(matrix.chunks(7 * w), &thresholds)
+            .unwrap();
+        assert_eq!(res1.len(), res2.len());
+        for (band1, band2) in res1.iter().zip(res2.iter()) {
+            assert_eq!(band1.min_v(), band2.min_v());
+            assert_eq!(band1.max_v(), band2.max_v());
+            assert_eq!(band1.geometry(), band2.geometry());
+        }
+
+        let res3 = ContourBuilder::new(w, h)
+            .use_quad_tree(true)
+            .contours_from_strips(matrix.chunks(w), &thresholds)
+            .unwrap();
+        for (band1, band3) in res1.iter().zip(res3.iter()) {
+            assert_eq!(band1.geometry(), band3.geometry());
+        }
+
+        // Rings are emitted as soon as they are closed
+        let mut contourer = builder.strip_contourer(&thresholds).unwrap();
+        let mut n_rings = 0;
+        for strip in matrix.chunks(7 * w) {
+            n_rings += contourer.push_strip(strip).unwrap().len();
+            assert!(contourer.open_paths() <= 2 * w * (thresholds.len() - 1));
+        }
+        assert!(n_rings > 0);
+        n_rings += contourer.finish().unwrap().len();
+        let n_rings_expected: usize = res1
+            .iter()
+            .flat_map(|band| band.geometry().0.iter())
+            .map(|polygon| polygon.interiors().len() + 1)
+            .sum();
+        assert_eq!(n_rings, n_rings_expected);
+    }
+
+    #[test]
+    fn strip_contourer_nodata_and_errors() {
+        let matrix = vec![
+            vec![1., 1., 1., 1., 1., 1., 1.],
+            vec![1., 5., 5., 5., 5., 5., 1.],
+            vec![1., 5., -1., -1., -1., 5., 1.],
+            vec![1., 5., -1., -1., -1., 5., 1.],
+            vec![1., 5., 5., 5., 5., 5., 1.],
+            vec![1., 1., 1., 1., 1., 1., 1.],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let builder = ContourBuilder::new(width, height).nodata(-1.);
+
+        let mut contourer = builder.strip_contourer(&[0., 3., 7.]).unwrap();
+        let mut rings = contourer.push_strip(&matrix[..4 * width]).unwrap();
+        assert!(rings.is_empty());
+        rings.extend(contourer.push_strip(&matrix[4 * width..]).unwrap());
+        rings.extend(contourer.finish().unwrap());
+
+        // As with `contours`, the dropped cells that no ring reaches are ignored
+        assert_eq!(rings.len(), 3);
+        assert_eq!(rings.iter().filter(|r| r.band == 0).count(), 2);
+        assert_eq!(rings.iter().filter(|r| r.band == 1).count(), 1);
+        for ring in &rings {
+            assert_eq!(crate::area::area(&ring.ring.0) > 0., ring.is_exterior);
+        }
+
+        // The hole around the missing data is cut half-way to them
+        let builder = builder.nodata_boundary(NoDataBoundary::HalfCell);
+        let mut contourer = builder.strip_contourer(&[0., 3., 7.]).unwrap();
+        let mut rings = contourer.push_strip(&matrix).unwrap();
+        rings.extend(contourer.finish().unwrap());
+        let band_rings = rings.iter().filter(|r| r.band == 1).collect::<Vec<_>>();
+        assert_eq!(band_rings.len(), 2);
+        assert_eq!(band_rings.iter().filter(|r| r.is_exterior).count(), 1);
+        let hole = band_rings.iter().find(|r| !r.is_exterior).unwrap();
+        assert_eq!(crate::area::area(&hole.ring.0), -11.);
+
+        // The strips must contain whole rows, and all the rows must be read
+        let mut contourer = builder.strip_contourer(&[0., 3., 7.]).unwrap();
+        assert!(contourer.push_strip(&matrix[..width + 1]).is_err());
+        contourer.push_strip(&matrix[..width]).unwrap();
+        assert!(contourer.finish().is_err());
+    }
+
+    #[cfg(feature = "wkt")]
+    #[test]
+    fn band_wkt_round_trip() {
+        use crate::Band;
+
+        let (matrix, width, height) = peak_grid();
+        let res = ContourBuilder::new(width, height)
+            .x_origin(0.1)
+            .y_step(1. / 3.)
+            .contours(&matrix, &[3., 7., 16.])
+            .unwrap();
+
+        for band in res.iter() {
+            let wkt = band.to_wkt();
+            assert!(wkt.starts_with("MULTIPOLYGON((("));
+            let band2 = Band::from_wkt(&wkt, band.min_v(), band.max_v()).unwrap();
+            assert_eq!(band.geometry(), band2.geometry());
+        }
+
+        let band = Band::from_wkt(" polygon ((0 0, 0 1,1 1 , 1e0 -0 ,0 0)) ", 0., 1.).unwrap();
+        assert_eq!(band.to_wkt(), "MULTIPOLYGON(((0 0,0 1,1 1,1 -0,0 0)))");
+        let band = Band::from_wkt("MULTIPOLYGON EMPTY", 0., 1.).unwrap();
+        assert_eq!(band.to_wkt(), "MULTIPOLYGON EMPTY");
+
+        // Empty bands and polygons without exterior ring
+        let square = |min: f64, max: f64| {
+            LineString::from(vec![
+                (min, min),
+                (min, max),
+                (max, max),
+                (max, min),
+                (min, min),
+            ])
+        };
+        for (geometry, wkt) in [
+            (MultiPolygon(vec![]), "MULTIPOLYGON EMPTY"),
+            (
+                MultiPolygon(vec![Polygon::new(LineString(vec![]), vec![])]),
+                "MULTIPOLYGON(EMPTY)",
+            ),
+            (
+                MultiPolygon(vec![
+                    Polygon::new(LineString(vec![]), vec![square(1., 2.)]),
+                    Polygon::new(square(0., 3.), vec![square(1., 2.), LineString(vec![])]),
+                ]),
+                "MULTIPOLYGON(EMPTY,((0 0,0 3,3 3,3 0,0 0),(1 1,1 2,2 2,2 1,1 1)))",
+            ),
+        ] {
+            let band = Band {
+                min_v: 0.,
+                max_v: 1.,
+                geometry,
+            };
+            assert_eq!(band.to_wkt(), wkt);
+            let band2 = Band::from_wkt(wkt, 0., 1.).unwrap();
+            assert_eq!(band2.to_wkt(), wkt);
+        }
+        let band = Band::from_wkt("MULTIPOLYGON(EMPTY,((0 0,0 1,1 1,0 0)))", 0., 1.).unwrap();
+        assert_eq!(
+            band.geometry().0,
+            vec![
+                Polygon::new(LineString(vec![]), vec![]),
+                Polygon::new(
+                    LineString::from(vec![(0., 0.), (0., 1.), (1., 1.), (0., 0.)]),
+                    vec![]
+                ),
+            ]
+        );
+        assert!(Band::from_wkt("LINESTRING(0 0,1 1)", 0., 1.).is_err());
+        assert!(Band::from_wkt("POLYGON((0 0,0 1,1 1,0 0)", 0., 1.).is_err());
+    }
+
+    #[cfg(feature = "wkb")]
+    #[test]
+    fn band_wkb_round_trip() {
+        use crate::Band;
+
+        let (matrix, width, height) = peak_grid();
+        let res = ContourBuilder::new(width, height)
+            .x_origin(0.1)
+            .y_step(1. / 3.)
+            .contours(&matrix, &[3., 7., 16.])
+            .unwrap();
+
+        for band in res.iter() {
+            let wkb = band.to_wkb();
+            let band2 = Band::from_wkb(&wkb, band.min_v(), band.max_v()).unwrap();
+            assert_eq!(band.geometry(), band2.geometry());
+        }
+
+        // Empty bands and polygons without exterior ring
+        let square = |min: f64, max: f64| {
+            LineString::from(vec![
+                (min, min),
+                (min, max),
+                (max, max),
+                (max, min),
+                (min, min),
+            ])
+        };
+        let empty = Polygon::new(LineString(vec![]), vec![]);
+        for (geometry, expected) in [
+            (MultiPolygon(vec![]), MultiPolygon(vec![])),
+            (
+                MultiPolygon(vec![empty.clone()]),
+                MultiPolygon(vec![empty.clone()]),
+            ),
+            (
+                MultiPolygon(vec![
+                    Polygon::new(LineString(vec![]), vec![square(1., 2.)]),
+                    Polygon::new(square(0., 3.), vec![square(1., 2.), LineString(vec![])]),
+                ]),
+                MultiPolygon(vec![
+                    empty.clone(),
+                    Polygon::new(square(0., 3.), vec![square(1., 2.)]),
+                ]),
+            ),
+        ] {
+            let band = Band {
+                min_v: 0.,
+                max_v: 1.,
+                geometry,
+            };
+            let band2 = Band::from_wkb(&band.to_wkb(), 0., 1.).unwrap();
+            assert_eq!(band2.geometry(), &expected);
+        }
+        // A Polygon without rings
+        let band = Band {
+            min_v: 0.,
+            max_v: 1.,
+            geometry: MultiPolygon(vec![empty]),
+        };
+        assert_eq!(
+            band.to_wkb(),
+            vec![1, 6, 0, 0, 0, 1, 0, 0, 0, 1, 3, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        // A big endian Polygon
+        let mut wkb = vec![0, 0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 4];
+        for (x, y) in [(0., 0.), (0., 1.), (1., 1.), (0., 0.)] {
+            wkb.extend_from_slice(&f64::to_be_bytes(x));
+            wkb.extend_from_slice(&f64::to_be_bytes(y));
+        }
+        let band = Band::from_wkb(&wkb, 0., 1.).unwrap();
+        assert_eq!(
+            band.geometry(),
+            &MultiPolygon(vec![Polygon::new(
+                LineString::from(vec![(0., 0.), (0., 1.), (1., 1.), (0., 0.)]),
+                vec![]
+            )])
+        );
+        assert!(Band::from_wkb(&wkb[..wkb.len() - 1], 0., 1.).is_err());
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn bands_to_svg() {
+        use crate::{ColorRamp, SvgRenderer};
+
+        let (matrix, width, height) = peak_grid();
+        let builder = ContourBuilder::new(width, height)
+            .x_origin(10.)
+            .y_origin(20.)
+            .x_step(2.)
+            .y_step(-2.);
+        let res = builder.contours(&matrix, &[0., 3., 7., 16.]).unwrap();
+
+        let svg = SvgRenderer::new()
+            .width(300)
+            .grid(&builder)
+            .color_ramp(ColorRamp::greys())
+            .legend(true)
+            .render(&res);
+
+        // The grid goes from (10, 10) to (22, 20), with the y axis pointing up
+        assert!(svg.contains(r#"<svg width="300" height="250" viewBox="10 -20 12 10">"#));
+        assert_eq!(svg.matches("<path ").count(), 3);
+        assert_eq!(svg.matches(r#"fill-rule="evenodd""#).count(), 3);
+        // Colors are taken from the ramp according to the middle of each band
+        assert!(
+            svg.contains(r##"fill="#e7e7e7" fill-rule="evenodd" data-min-v="0" data-max-v="3""##)
+        );
+        assert!(
+            svg.contains(r##"fill="#484848" fill-rule="evenodd" data-min-v="7" data-max-v="16""##)
+        );
+        // The band with holes has one subpath per ring
+        let path = svg
+            .lines()
+            .find(|l| l.contains(r#"data-min-v="3""#))
+            .unwrap();
+        let n_rings: usize = res[1]
+            .geometry()
+            .0
+            .iter()
+            .map(|p| p.interiors().len() + 1)
+            .sum();
+        assert!(n_rings > 1);
+        assert_eq!(path.matches('M').count(), n_rings);
+        assert_eq!(path.matches('Z').count(), n_rings);
+        assert_eq!(svg.matches("<rect ").count(), 3);
+
+        // The open bands are colored at the ends of the ramp
+        let res = builder
+            .contours(&matrix, &[f64::NEG_INFINITY, 3., 7., f64::INFINITY])
+            .unwrap();
+        let svg = SvgRenderer::new()
+            .color_ramp(ColorRamp::greys())
+            .stroke(r##"url("#a")&b"##)
+            .render(&res);
+        assert!(svg.contains(r##"fill="#ffffff" fill-rule="evenodd""##));
+        assert!(svg.contains(r##"fill="#808080" fill-rule="evenodd""##));
+        assert!(svg.contains(r##"fill="#000000" fill-rule="evenodd""##));
+        // The stroke is escaped
+        assert!(svg.contains(r##" stroke="url(&quot;#a&quot;)&amp;b" "##));
+
+        assert_eq!(ColorRamp::greys().color(0.5), [128, 128, 128]);
+    }
+
+    #[test]
+    fn thresholds_classifications() {
+        use crate::{thresholds, Classification};
+
+        let values = [f64::NAN, 1., 2., 3., -9999., 4., 5., 6., 7., 8., 9., 10.];
+        let nodata = Some(-9999.);
+        let classify = |classification| thresholds(&values, classification, nodata).unwrap();
+
+        assert_eq!(
+            classify(Classification::EqualInterval(3)),
+            vec![1., 4., 7., 10.]
+        );
+        assert_eq!(classify(Classification::Quantile(2)), vec![1., 6., 10.]);
+        let std_dev = classify(Classification::StandardDeviation(1.));
+        let sd = 8.25f64.sqrt();
+        assert_eq!(std_dev.len(), 5);
+        assert_eq!(std_dev[2], 5.5);
+        assert!((std_dev[1] - (5.5 - sd)).abs() < 1e-12);
+        assert!((std_dev[3] - (5.5 + sd)).abs() < 1e-12);
+
+        let clustered = [1., 2., 3., 10., 11., 12., 30., 31.];
+        assert_eq!(
+            thresholds(&clustered, Classification::Jenks(3), None).unwrap(),
+            vec![1., 10., 30., 31.]
+        );
+        let geometric = thresholds(&[1u16, 50, 100], Classification::Geometric(2), None).unwrap();
+        assert_eq!(geometric.len(), 3);
+        assert!((geometric[1] - 10.).abs() < 1e-12);
+        assert_eq!(
+            thresholds(
+                &[1., 1., 1., 1., 1., 1., 1., 1., 2., 10.],
+                Classification::HeadTail,
+                None
+            )
+            .unwrap(),
+            vec![1., 2., 10.]
+        );
+
+        // The no-data value is compared in the type of the values
+        let values_f32 = [1f32, -9999.9, 2., 3., -9999.9];
+        assert_eq!(
+            thresholds(&values_f32, Classification::EqualInterval(2), Some(-9999.9)).unwrap(),
+            vec![1., 2., 3.]
+        );
+
+        // Values that are masked are ignored too
+        let builder = ContourBuilder::new(3, 4).nodata(-9999.).mask(&[
+            true, true, true, true, true, true, true, true, true, true, true, false,
+        ]);
+        assert_eq!(
+            builder
+                .thresholds(&values, Classification::EqualInterval(2))
+                .unwrap(),
+            vec![1., 5., 9.]
+        );
+
+        assert!(thresholds(&values, Classification::EqualInterval(0), None).is_err());
+        assert!(thresholds(&[0., 1.], Classification::Geometric(2), None).is_err());
+        assert!(thresholds(&[f64::NAN], Classification::Quantile(2), None).is_err());
+        assert_eq!(
+            thresholds(&[2., 2.], Classification::Jenks(2), None).unwrap(),
+            vec![2., 2.]
+        );
+    }
+
+    #[test]
+    fn nice_thresholds_round_steps() {
+        use crate::{nice_thresholds, thresholds, Classification};
+
+        assert_eq!(
+            nice_thresholds(&[94., f64::NAN, 195.], 20).unwrap(),
+            (18..=39).map(|i| i as f64 * 5.).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            nice_thresholds(&[0., 97.34], 4).unwrap(),
+            vec![0., 25., 50., 75., 100.]
+        );
+        assert_eq!(
+            nice_thresholds(&[3i32, 1003], 10).unwrap(),
+            (0..=11).map(|i| i as f64 * 100.).collect::<Vec<_>>()
+        );
+        // No floating point artifacts with steps lower than 1
+        assert_eq!(
+            nice_thresholds(&[0.02, 0.97], 10).unwrap(),
+            vec![0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.]
+        );
+        assert_eq!(
+            thresholds(&[-9999., -12., 7.], Classification::Nice(2), Some(-9999.)).unwrap(),
+            vec![-20., -10., 0., 10.]
+        );
+        // Infinite values are ignored
+        assert_eq!(
+            nice_thresholds(&[f64::NEG_INFINITY, 0., 97.34, f64::INFINITY], 4).unwrap(),
+            vec![0., 25., 50., 75., 100.]
+        );
+        assert_eq!(
+            thresholds(
+                &[f64::INFINITY, 1., 3.],
+                Classification::EqualInterval(2),
+                None
+            )
+            .unwrap(),
+            vec![1., 2., 3.]
+        );
+        assert!(nice_thresholds(&[f64::INFINITY], 4).is_err());
+    }
+
+    #[test]
+    fn band_stats() {
+        let matrix = vec![
+            vec![0., 0., 0., 0., 0.],
+            vec![0., 1., 1., 1., 0.],
+            vec![0., 1., 0., 1., 0.],
+            vec![0., 1., 1., 1., 0.],
+            vec![0., 0., 0., 0., 0.],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let res = ContourBuilder::new(width, height)
+            .x_origin(100.)
+            .x_step(2.)
+            .contours(&matrix, &[0.5, 1.])
+            .unwrap();
+
+        // A ring through the middle of the edges around the 1 values,
+        // with a diamond-shaped hole around the central 0 value
+        let stats = res[0].stats();
+        assert_eq!(stats.polygons, 1);
+        assert_eq!(stats.holes, 1);
+        assert_eq!(stats.vertices, 12 + 4);
+        // The exterior ring is a 3x3 square (in grid units) with cut corners
+        // and the hole a diamond whose diagonals are 1 grid unit long
+        assert!((stats.area - 2. * (9. - 4. * 0.125 - 0.5)).abs() < 1e-12);
+        assert_eq!(
+            stats.bbox,
+            Some(geo_types::Rect::new((101., 0.5), (107., 3.5)))
+        );
+        let expected_perimeter = 2. * (4. + 2.) + 8. * 1f64.hypot(0.5);
+        assert!((stats.perimeter - expected_perimeter).abs() < 1e-12);
+
+        let empty = ContourBuilder::new(width, height)
+            .contours(&matrix, &[2., 3.])
+            .unwrap();
+        let stats = empty[0].stats();
+        assert_eq!((stats.polygons, stats.area, stats.bbox), (0, 0., None));
+    }
+
+    #[test]
+    fn zonal_stats_weighted_by_coverage() {
+        let matrix = vec![vec![0., 0., 0.], vec![0., 1., 0.], vec![0., 0., 0.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let values = (1..=9).map(|v| v as f64).collect::<Vec<_>>();
+
+        let res = ContourBuilder::new(width, height)
+            .zonal_stats(&matrix, &values, &[0., 0.5, 1.])
+            .unwrap();
+        assert_eq!(res.len(), 2);
+
+        // The upper band is a diamond (whose area is half a pixel)
+        // inside the pixel of the central point
+        assert_eq!((res[1].min_v, res[1].max_v), (0.5, 1.));
+        assert!((res[1].count - 0.5).abs() < 1e-12);
+        assert!((res[1].sum - 2.5).abs() < 1e-12);
+        assert!((res[1].mean - 5.).abs() < 1e-12);
+        assert_eq!((res[1].min, res[1].max), (5., 5.));
+
+        // The lower band covers the rest of the grid
+        assert!((res[0].count - 8.5).abs() < 1e-12);
+        assert!((res[0].sum - 42.5).abs() < 1e-12);
+        assert!((res[0].mean - 5.).abs() < 1e-12);
+        assert_eq!((res[0].min, res[0].max), (1., 9.));
+
+        let empty = ContourBuilder::new(width, height)
+            .zonal_stats(&matrix, &values, &[2., 3.])
+            .unwrap();
+        assert_eq!(empty[0].count, 0.);
+        assert!(empty[0].mean.is_nan());
+
+        assert!(ContourBuilder::new(width, height)
+            .zonal_stats(&matrix, &values[..8], &[0., 1.])
+            .is_err());
+    }
+
+    #[test]
+    /// Test that the population inside the bands sums up to the total population
+    /// (dataset from https://observablehq.com/@mthh/stewarts-potentials-on-the-gpu)
+    fn zonal_stats_pot_pop() {
+        let (matrix, w, h) = pot_pop();
+
+        // Use the potentials themselves as the secondary grid
+        let min = matrix.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = matrix.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let intervals = [
+            min, 105483.25, 527416.25, 1054832.5, 2109665., 3164497.5, 4219330., 5274162.5,
+            6328995., 7383827.5, 8438660., 9704459., max,
+        ];
+        let res = ContourBuilder::new(w, h)
+            .zonal_stats(&matrix, &matrix, &intervals)
+            .unwrap();
+
+        let count = res.iter().map(|s| s.count).sum::<f64>();
+        let sum = res.iter().map(|s| s.sum).sum::<f64>();
+        assert!((count - (w * h) as f64).abs() < 1e-6);
+        assert!((sum - matrix.iter().sum::<f64>()).abs() / sum < 1e-9);
+        for stats in res.iter() {
+            assert!(stats.count > 0.);
+            assert!(stats.mean > stats.min_v && stats.mean < stats.max_v);
+        }
+    }
+
+    #[test]
+    /// Test that the bands don't overlap, and cover the whole grid,
+    /// on a grid with many saddle cells
+    fn zonal_stats_bands_partition_grid() {
+        let (width, height) = (40, 30);
+        let mut seed = 12345u64;
+        let matrix = (0..width * height)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((seed >> 33) % 10) as f64
+            })
+            .collect::<Vec<_>>();
+        let ones = vec![1.; width * height];
+
+        for thresholds in [
+            vec![0., 1., 2., 3., 4., 5., 6., 7., 8., 9.],
+            vec![0., 0.75, 1.75, 2.75, 3.75, 4.75, 5.75, 6.75, 7.75, 9.],
+        ] {
+            let res = ContourBuilder::new(width, height)
+                .zonal_stats(&matrix, &ones, &thresholds)
+                .unwrap();
+            let count = res.iter().map(|s| s.count).sum::<f64>();
+            assert!((count - (width * height) as f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn cell_coverage_fractions() {
+        let matrix = vec![vec![0., 0., 0.], vec![0., 1., 0.], vec![0., 0., 0.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+
+        let res = ContourBuilder::new(width, height)
+            .cell_coverage(&matrix, &[0., 0.5, 1.])
+            .unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!((res[1].min_v, res[1].max_v), (0.5, 1.));
+        assert_eq!((res[1].width, res[1].height), (2, 2));
+        // A quarter of the diamond in each cell
+        for (lower, upper) in res[0].fractions.iter().zip(res[1].fractions.iter()) {
+            assert!((upper - 0.125).abs() < 1e-12);
+            assert!((lower - 0.875).abs() < 1e-12);
+        }
+        assert_eq!(res[1].fraction(1, 0), res[1].fractions[1]);
+    }
+
+    #[test]
+    /// Test that the area covered by the bands in the cells
+    /// is the area of the polygons of the bands (volcano dataset)
+    fn cell_coverage_volcano_same_area_as_bands() {
+        let (matrix, w, h) = volcano();
+
+        let intervals = [
+            90., 100., 110., 120., 130., 140., 150., 160., 170., 180., 190., 200.,
+        ];
+        let builder = ContourBuilder::new(w, h);
+        let bands = builder.contours(&matrix, &intervals).unwrap();
+        let coverage = builder.cell_coverage(&matrix, &intervals).unwrap();
+
+        for (band, coverage) in bands.iter().zip(coverage.iter()) {
+            let area = coverage.fractions.iter().sum::<f64>();
+            assert!((band.stats().area - area).abs() < 1e-9 * area.max(1.));
+            assert!(coverage.fractions.iter().all(|f| (0. ..=1.).contains(f)));
+        }
+    }
+
+    #[cfg(feature = "topojson")]
+    #[test]
+    fn bands_to_topojson_shared_arcs() {
+        use crate::TopoJsonWriter;
+
+        let matrix = vec![vec![0., 0., 0.], vec![0., 1., 0.], vec![0., 0., 0.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let bands = ContourBuilder::new(width, height)
+            .contours(&matrix, &[0., 0.5, 1.])
+            .unwrap();
+
+        let topology = TopoJsonWriter::new()
+            .quantization(5)
+            .object_name("bands")
+            .write(&bands);
+        assert_eq!(topology["type"], "Topology");
+        assert_eq!(topology["bbox"], serde_json::json!([0., 0., 2., 2.]));
+        assert_eq!(
+            topology["transform"],
+            serde_json::json!({"scale": [0.5, 0.5], "translate": [0., 0.]})
+        );
+
+        // The boundary of the grid and the diamond, shared by both bands
+        assert_eq!(topology["arcs"].as_array().unwrap().len(), 2);
+        let geometries = &topology["objects"]["bands"]["geometries"];
+        assert_eq!(geometries[0]["properties"]["min_v"], 0.);
+        assert_eq!(geometries[1]["properties"]["max_v"], 1.);
+        let hole = geometries[0]["arcs"][0][1][0].as_i64().unwrap();
+        let diamond = geometries[1]["arcs"][0][0][0].as_i64().unwrap();
+        assert_eq!(hole, !diamond);
+        // Delta-encoded positions of the diamond
+        let arc = &topology["arcs"][hole.max(diamond) as usize];
+        let mut position = [0, 0];
+        let positions = arc
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|delta| {
+                position[0] += delta[0].as_i64().unwrap();
+                position[1] += delta[1].as_i64().unwrap();
+                position
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions[0], positions[4]);
+        for p in [[1, 2], [2, 1], [3, 2], [2, 3]] {
+            assert!(positions.contains(&p));
+        }
+    }
+
+    #[cfg(feature = "topojson")]
+    #[test]
+    /// Test that the shared boundaries of the bands are only stored once (volcano dataset)
+    fn bands_to_topojson_volcano() {
+        use crate::TopoJsonWriter;
+
+        let (matrix, w, h) = volcano();
+
+        let intervals = [
+            90., 100., 110., 120., 130., 140., 150., 160., 170., 180., 190., 200.,
+        ];
+        let bands = ContourBuilder::new(w, h)
+            .contours(&matrix, &intervals)
+            .unwrap();
+        let topology = TopoJsonWriter::new().write(&bands);
+
+        let n_points = bands
+            .iter()
+            .flat_map(|band| band.geometry().0.iter())
+            .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors().iter()))
+            .map(|ring| ring.0.len())
+            .sum::<usize>();
+        let n_arc_points = topology["arcs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|arc| arc.as_array().unwrap().len())
+            .sum::<usize>();
+        assert!(n_arc_points < n_points * 6 / 10);
+
+        // Each arc is used once or twice (in opposite directions)
+        let mut uses = vec![0; topology["arcs"].as_array().unwrap().len()];
+        for geometry in topology["objects"]["isobands"]["geometries"]
+            .as_array()
+            .unwrap()
+        {
+            for arc in geometry["arcs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .flat_map(|polygon| polygon.as_array().unwrap())
+                .flat_map(|ring| ring.as_array().unwrap())
+            {
+                let arc = arc.as_i64().unwrap();
+                uses[if arc < 0 { !arc } else { arc } as usize] += 1;
+            }
+        }
+        assert!(uses.iter().all(|&n| n == 1 || n == 2));
+    }
+
+    #[test]
+    /// Test that the simplified bands keep covering the grid without gaps nor overlaps
+    /// (volcano dataset)
+    fn contours_simplified_volcano() {
+        let (matrix, w, h) = volcano();
+
+        let intervals = [
+            90., 100., 110., 120., 130., 140., 150., 160., 170., 180., 190., 200.,
+        ];
+        let bands = ContourBuilder::new(w, h)
+            .contours(&matrix, &intervals)
+            .unwrap();
+        let simplified = ContourBuilder::new(w, h)
+            .simplify(1.)
+            .contours(&matrix, &intervals)
+            .unwrap();
+
+        let total = |bands: &[crate::Band]| {
+            bands.iter().fold((0., 0), |(area, vertices), band| {
+                let stats = band.stats();
+                (area + stats.area, vertices + stats.vertices)
+            })
+        };
+        let (area, vertices) = total(&bands);
+        let (simplified_area, simplified_vertices) = total(&simplified);
+        // The bands cover the whole grid, before and after the simplification
+        assert!((area - ((w - 1) * (h - 1)) as f64).abs() < 1e-6);
+        assert!((simplified_area - area).abs() < 1e-6);
+        assert!(simplified_vertices * 4 < vertices);
+
+        for (band, simplified_band) in bands.iter().zip(simplified.iter()) {
+            assert_eq!(band.geometry().0.len(), simplified_band.geometry().0.len());
+            for polygon in simplified_band.geometry().0.iter() {
+                for hole in polygon.interiors() {
+                    assert!(crate::contains::contains(&polygon.exterior().0, &hole.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn contours_smoothed_volcano() {
+        let (matrix, w, h) = volcano();
+
+        let intervals = [
+            90., 100., 110., 120., 130., 140., 150., 160., 170., 180., 190., 200.,
+        ];
+        let bands = ContourBuilder::new(w, h)
+            .contours(&matrix, &intervals)
+            .unwrap();
+        let on_frame = |c: &geo_types::Coord<f64>| {
+            c.x == 0. || c.y == 0. || c.x == (w - 1) as f64 || c.y == (h - 1) as f64
+        };
+
+        for smoothing in [Smoothing::Chaikin(3), Smoothing::CatmullRom(4)] {
+            let smoothed = ContourBuilder::new(w, h)
+                .smooth(smoothing)
+                .contours(&matrix, &intervals)
+                .unwrap();
+
+            let mut area = 0.;
+            for (band, smoothed_band) in bands.iter().zip(smoothed.iter()) {
+                let (stats, smoothed_stats) = (band.stats(), smoothed_band.stats());
+                area += smoothed_stats.area;
+                assert_eq!(stats.polygons, smoothed_stats.polygons);
+                assert!(smoothed_stats.vertices > 2 * stats.vertices);
+
+                // The points on the frame of the grid are not moved (the points of the
+                // boundaries shared by adjacent bands may be moved by a tiny amount)
+                let frame_points = |band: &Band| {
+                    let mut points = band
+                        .geometry()
+                        .0
+                        .iter()
+                        .flat_map(|polygon| polygon.exterior().0.iter())
+                        .filter(|c| on_frame(c))
+                        .map(|c| ((c.x * 1e6).round() as i64, (c.y * 1e6).round() as i64))
+                        .collect::<Vec<_>>();
+                    points.sort_unstable();
+                    points.dedup();
+                    points
+                };
+                assert_eq!(frame_points(band), frame_points(smoothed_band));
+            }
+            // The smoothed bands still cover the whole grid, without gaps nor overlaps
+            assert!((area - ((w - 1) * (h - 1)) as f64).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn contours_interval_closure() {
+        let matrix = vec![
+            vec![0., 0., 0., 0.],
+            vec![0., 1., 1., 0.],
+            vec![0., 1., 1., 0.],
+            vec![0., 0., 0., 0.],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let area = |bands: &[Band]| bands.iter().map(|b| b.stats().area).collect::<Vec<_>>();
+
+        // By default, the values equal to 1 belong to the band [1, 2]
+        let left = ContourBuilder::new(width, height)
+            .contours(&matrix, &[0., 1., 2.])
+            .unwrap();
+        let left_area = area(&left);
+        assert!((left_area[0] - 8.).abs() < 1e-9);
+        assert!((left_area[1] - 1.).abs() < 1e-9);
+
+        // With right-closed intervals, they belong to the band [0, 1]
+        let right = ContourBuilder::new(width, height)
+            .interval_closure(IntervalClosure::Right)
+            .contours(&matrix, &[0., 1., 2.])
+            .unwrap();
+        assert!((area(&right)[0] - 9.).abs() < 1e-9);
+        assert!(right[1].geometry().0.is_empty());
+
+        // A larger tolerance moves the excluded bound: the band (0.5, 1]
+        // only contains the values equal to 1
+        let tolerant = ContourBuilder::new(width, height)
+            .interval_closure(IntervalClosure::Right)
+            .interval_tolerance(0.5)
+            .contours(&matrix, &[0., 0.5, 1.])
+            .unwrap();
+        let tolerant_area = area(&tolerant);
+        assert!((tolerant_area[0] - 5.5).abs() < 1e-9);
+        assert!((tolerant_area[1] - 1.).abs() < 1e-9);
+
+        // Open-ended bands, for the values below and above 0.5
+        // (the latter being an octagon around the values equal to 1)
+        for (closure, use_quad_tree) in [
+            (IntervalClosure::Left, false),
+            (IntervalClosure::Right, true),
+        ] {
+            let open = ContourBuilder::new(width, height)
+                .interval_closure(closure)
+                .use_quad_tree(use_quad_tree)
+                .contours(&matrix, &[f64::NEG_INFINITY, 0.5, f64::INFINITY])
+                .unwrap();
+            assert_eq!(open[0].min_v(), f64::NEG_INFINITY);
+            assert_eq!(open[1].max_v(), f64::INFINITY);
+            let open_area = area(&open);
+            assert!((open_area[0] - 5.5).abs() < 1e-9);
+            assert!((open_area[1] - 3.5).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn contours_saddle_resolution() {
+        // A saddle cell whose center average (0.9025) is inside the band [0.6, 3],
+        // but whose bilinear saddle point (1.83 / 3.61) is below it
+        let matrix = vec![vec![3., 0.], vec![0., 0.61]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+
+        for (saddle_resolution, n_polygons) in [
+            (SaddleResolution::CenterAverage, 1),
+            (SaddleResolution::AsymptoticDecider, 2),
+            (SaddleResolution::Connect, 1),
+            (SaddleResolution::Separate, 2),
+        ] {
+            let res = ContourBuilder::new(width, height)
+                .saddle_resolution(saddle_resolution)
+                .contours(&matrix, &[0., 0.6, 3.])
+                .unwrap();
+            assert_eq!(res[1].geometry().0.len(), n_polygons);
+            // The adjacent bands are resolved consistently, without gaps nor overlaps
+            // (the band below is separated when the band above is connected)
+            let area = res[0].stats().area + res[1].stats().area;
+            assert!((area - 1.).abs() < 1e-9);
+            assert_eq!(res[0].geometry().0.len(), 3 - n_polygons);
+
+            let res = ContourBuilder::new(width, height)
+                .saddle_resolution(saddle_resolution)
+                .contours_from_strips(matrix.chunks(width), &[0., 0.6, 3.])
+                .unwrap();
+            assert_eq!(res[1].geometry().0.len(), n_polygons);
+        }
+    }
+
+    #[test]
+    fn contours_logarithmic_interpolation() {
+        let matrix = vec![vec![1., 100.], vec![1., 100.]];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+
+        for (interpolation, x) in [
+            (Interpolation::Linear, 9. / 99.),
+            (Interpolation::Logarithmic, 0.5),
+        ] {
+            let builder = ContourBuilder::new(width, height).interpolation(interpolation);
+            let res = builder.contours(&matrix, &[1., 10., 100.]).unwrap();
+            assert!((res[0].stats().area - x).abs() < 1e-9);
+            assert!((res[1].stats().area - (1. - x)).abs() < 1e-9);
+
+            let lines = builder.isolines(&matrix, &[10.]).unwrap();
+            for point in lines[0].geometry().0[0].points() {
+                assert!((point.x() - x).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn contours_pixelated() {
+        let matrix = vec![
+            vec![0., 0., 1., 1.],
+            vec![0., 1., 1., 0.],
+            vec![1., 0., 0., 0.],
+        ];
+        let (matrix, width, height) = make_grid_from2d_vec(&matrix);
+        let builder = ContourBuilder::new(width, height).interpolation(Interpolation::Pixelated);
+        let is_half_integer = |v: f64| (2. * v).fract() == 0.;
+
+        let res = builder.contours(&matrix, &[-0.5, 0.5, 2.]).unwrap();
+        // The pixels on the frame of the grid are clipped to the extent of the grid
+        assert_eq!(res[0].stats().area, 3.);
+        assert_eq!(res[1].stats().area, 3.);
+        // The pixel (0, 2) only touches the other pixels of the band by a corner
+        assert_eq!(res[1].geometry().0.len(), 2);
+        for band in res.iter() {
+            for polygon in band.geometry().0.iter() {
+                for point in polygon.exterior().points() {
+                    assert!(is_half_integer(point.x()) && is_half_integer(point.y()));
+                }
+            }
+        }
+
+        // The boundaries of the bands are stair-stepped
+        for polygon in res[1].geometry().0.iter() {
+            for segment in polygon.exterior().lines() {
+                assert!(segment.start.x == segment.end.x || segment.start.y == segment.end.y);
+            }
+        }
+        let res_quad_tree = ContourBuilder::new(width, height)
+            .interpolation(Interpolation::Pixelated)
+            .use_quad_tree(true)
+            .contours(&matrix, &[-0.5, 0.5, 2.])
+            .unwrap();
+        for (a, b) in res.iter().zip(res_quad_tree.iter()) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+
+        let coverage = builder.cell_coverage(&matrix, &[-0.5, 0.5, 2.]).unwrap();
+        assert_eq!(coverage[1].fractions.iter().sum::<f64>(), 3.);
+
+        // With the half-cell boundary, the pixel of a missing value is removed from the band
+        let mut matrix_nan = matrix.clone();
+        matrix_nan[6] = f64::NAN;
+        let res = ContourBuilder::new(width, height)
+            .interpolation(Interpolation::Pixelated)
+            .nodata_boundary(NoDataBoundary::HalfCell)
+            .contours(&matrix_nan, &[-0.5, 0.5, 2.])
+            .unwrap();
+        assert_eq!(res[1].stats().area, 2.);
+
+        // The isolines follow the boundaries of the pixels
+        let lines = builder.isolines(&matrix, &[0.5]).unwrap();
+        assert_eq!(lines[0].geometry().0.len(), 2);
+        for line in lines[0].geometry().0.iter() {
+            for segment in line.lines() {
+                assert!(is_half_integer(segment.start.x) && is_half_integer(segment.start.y));
+                assert!(segment.start.x == segment.end.x || segment.start.y == segment.end.y);
+            }
+        }
+    }
+
+    #[test]
+    fn classes() {
+        let matrix: Vec<u8> = vec![1, 1, 2, 2, 1, 3, 3, 2, 2, 1, 3, 3];
+        let builder = ContourBuilder::new(4, 3);
+
+        let res = builder.classes(&matrix).unwrap();
+        let classes = res.iter().map(|band| band.min_v()).collect::<Vec<_>>();
+        assert_eq!(classes, vec![1., 2., 3.]);
+        let areas = res.iter().map(|band| band.stats().area).collect::<Vec<_>>();
+        assert_eq!(areas, vec![1.75, 1.5, 2.75]);
+        // The pixels only touching by a corner are kept separate
+        let n_polygons = res
+            .iter()
+            .map(|band| band.geometry().0.len())
+            .collect::<Vec<_>>();
+        assert_eq!(n_polygons, vec![2, 2, 1]);
+        let res_quad_tree = ContourBuilder::new(4, 3)
+            .use_quad_tree(true)
+            .classes(&matrix)
+            .unwrap();
+        for (a, b) in res.iter().zip(res_quad_tree.iter()) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+
+        // Missing values are outside of all the classes
+        let res = builder
+            .nodata(3)
+            .nodata_boundary(NoDataBoundary::HalfCell)
+            .classes(&matrix)
+            .unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].stats().area + res[1].stats().area, 3.25);
+    }
+
+    #[test]
+    fn prepared_grid_volcano() {
+        let (matrix, w, h) = volcano();
+
+        for use_quad_tree in [false, true] {
+            let builder = ContourBuilder::new(w, h).use_quad_tree(use_quad_tree);
+            let mut borrowed = builder.prepare(&matrix[..]).unwrap();
+            let mut owned = builder.prepare(matrix.clone()).unwrap();
+
+            // The same prepared grid is used with several sets of thresholds
+            for thresholds in [
+                vec![90., 120., 150., 180., 200.],
+                vec![100., 105., 110., 115.],
+                vec![90., 200.],
+            ] {
+                let expected = builder.contours(&matrix, &thresholds).unwrap();
+                for res in [
+                    borrowed.contours(&thresholds).unwrap(),
+                    owned.contours(&thresholds).unwrap(),
+                ] {
+                    assert_eq!(res.len(), expected.len());
+                    for (band, expected) in res.iter().zip(expected.iter()) {
+                        assert_eq!(band.min_v(), expected.min_v());
+                        assert_eq!(band.geometry(), expected.geometry());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn update_isobands_volcano() {
+        let (original, w, h) = volcano();
+        let thresholds = [0., 90., 130., 150., 170., 200.];
+
+        for builder in [
+            ContourBuilder::new(w, h),
+            ContourBuilder::new(w, h)
+                .x_origin(10.)
+                .y_origin(50.)
+                .x_step(0.5)
+                .y_step(-0.5)
+                .nodata_boundary(NoDataBoundary::HalfCell),
+            ContourBuilder::new(w, h)
+                .interpolation(Interpolation::Pixelated)
+                .use_quad_tree(true),
+            ContourBuilder::new(w, h)
+                .geotransform([5., 0.8, 0.6, 40., -0.6, 0.8])
+                .nodata_boundary(NoDataBoundary::DropCell),
+        ] {
+            let mut matrix = original.clone();
+            let mut bands = builder.contours(&matrix, &thresholds).unwrap();
+            // The whole frame of the grid is initially inside the band [90, 130]:
+            // it leaves it after the second change and enters it again after the fourth one
+            for (dirty, change) in [
+                ([20, 10, 30, 25], 12.),
+                ([0, 0, 5, h], -20.),
+                ([40, 30, 50, 40], f64::NAN),
+                ([0, 0, 5, h], 20.),
+                ([60, 50, 62, 52], 1.),
+                ([0, 0, w, h], -100.),
+            ] {
+                for y in dirty[1]..dirty[3] {
+                    for x in dirty[0]..dirty[2] {
+                        matrix[y * w + x] += change;
+                    }
+                }
+                bands = builder
+                    .update_isobands(bands, &matrix, &thresholds, dirty)
+                    .unwrap();
+                let expected = builder.contours(&matrix, &thresholds).unwrap();
+                assert_eq!(bands.len(), expected.len());
+                for (band, expected) in bands.iter().zip(expected.iter()) {
+                    assert_eq!(band.min_v, expected.min_v);
+                    assert_eq!(band.max_v, expected.max_v);
+                    assert_eq!(band.geometry(), expected.geometry());
+                }
+            }
+
+            // Without previous bands, they are all computed
+            let bands = builder
+                .update_isobands(vec![], &matrix, &thresholds, [0, 0, 1, 1])
+                .unwrap();
+            let expected = builder.contours(&matrix, &thresholds).unwrap();
+            assert_eq!(bands.len(), expected.len());
+            for (band, expected) in bands.iter().zip(expected.iter()) {
+                assert_eq!(band.geometry(), expected.geometry());
+            }
+            // The bands must be the ones of the thresholds
+            let classes = builder.classes(&matrix[..]).unwrap();
+            assert!(builder
+                .update_isobands(classes, &matrix, &thresholds, [0, 0, 1, 1])
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn contours_series_volcano() {
+        let (matrix, w, h) = volcano();
+
+        let frames = [0., 10., 25., -15.]
+            .iter()
+            .map(|offset| matrix.iter().map(|v| v + offset).collect::<Vec<f64>>())
+            .collect::<Vec<_>>();
+        // The last band is empty in most frames
+        let thresholds = [80., 110., 140., 170., 200., 230.];
+
+        for use_quad_tree in [false, true] {
+            let builder = ContourBuilder::new(w, h).use_quad_tree(use_quad_tree);
+            let res = builder.contours_series(&frames, &thresholds).unwrap();
+            #[cfg(feature = "parallel")]
+            let par_res = builder.par_contours_series(&frames, &thresholds).unwrap();
+
+            assert_eq!(res.len(), frames.len());
+            for (i, frame) in frames.iter().enumerate() {
+                let expected = builder.contours(frame, &thresholds).unwrap();
+                assert_eq!(res[i].len(), thresholds.len() - 1);
+                for (band, expected) in res[i].iter().zip(expected.iter()) {
+                    assert_eq!(band.min_v(), expected.min_v());
+                    assert_eq!(band.geometry(), expected.geometry());
+                }
+                #[cfg(feature = "parallel")]
+                for (band, expected) in par_res[i].iter().zip(expected.iter()) {
+                    assert_eq!(band.geometry(), expected.geometry());
+                }
+            }
+        }
+    }
 }
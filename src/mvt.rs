@@ -0,0 +1,458 @@
+use crate::area::{area, clip_ring};
+use crate::errors::{new_error, ErrorKind, Result};
+use crate::isobands::Band;
+use geo_types::{Coord, LineString, Polygon};
+use rustc_hash::FxHashMap;
+
+/// Half of the circumference of the earth in Web Mercator (EPSG:3857)
+const HALF_CIRCUMFERENCE: f64 = std::f64::consts::PI * 6378137.;
+
+/// Maximum latitude of Web Mercator (where its extent is square), in degrees
+const MAX_LATITUDE: f64 = 85.0511287798066;
+
+// Commands of the MVT geometry encoding
+const MOVE_TO: u32 = 1;
+const LINE_TO: u32 = 2;
+const CLOSE_PATH: u32 = 7;
+
+// Geometry type of polygons in MVT features
+const POLYGON: u64 = 3;
+
+/// The coordinate reference system in which the bands are expressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MvtCoordinates {
+    /// Web Mercator (EPSG:3857) coordinates, in meters
+    #[default]
+    WebMercator,
+    /// Longitude / latitude (EPSG:4326) coordinates, in degrees
+    /// (the latitudes being clamped to the extent of Web Mercator, ±85.0511°)
+    LonLat,
+}
+
+/// Mapbox Vector Tile encoder, using builder pattern, to
+/// encode a `Slice` of [`Band`] as a layer of the z/x/y tile.
+///
+/// Polygons are clipped to the extent of the tile (plus a buffer),
+/// quantized to the tile grid and their rings are oriented following the
+/// MVT specification. Each band is encoded as a feature
+/// with its `min_v` and `max_v` values as attributes.
+pub struct MvtTile {
+    /// The zoom level of the tile
+    z: u8,
+    /// The column of the tile
+    x: u32,
+    /// The row of the tile
+    y: u32,
+    /// The size of the tile grid
+    extent: u32,
+    /// The size of the buffer around the tile (in tile grid units)
+    buffer: u32,
+    /// The name of the layer
+    layer_name: String,
+    /// The coordinates of the bands
+    coordinates: MvtCoordinates,
+}
+
+impl MvtTile {
+    /// Constructs a new encoder for the tile `z`/`x`/`y`
+    /// (the zoom level being at most 32, and the column and row lower than `2^z`).
+    ///
+    /// By default, `extent` is set to `4096`, `buffer` to `64`, `layer_name` to `"isobands"`
+    /// and the bands are expected to be in Web Mercator coordinates.
+    /// This can be changed using the corresponding methods.
+    pub fn new(z: u8, x: u32, y: u32) -> Result<Self> {
+        if z > 32 || x as u64 >= 1u64 << z || y as u64 >= 1u64 << z {
+            return Err(new_error(ErrorKind::BadTile));
+        }
+        Ok(MvtTile {
+            z,
+            x,
+            y,
+            extent: 4096,
+            buffer: 64,
+            layer_name: "isobands".to_string(),
+            coordinates: MvtCoordinates::default(),
+        })
+    }
+
+    /// Sets the size of the tile grid.
+    pub fn extent(mut self, extent: u32) -> Self {
+        self.extent = extent;
+        self
+    }
+
+    /// Sets the size of the buffer around the tile (in tile grid units).
+    pub fn buffer(mut self, buffer: u32) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// Sets the name of the layer.
+    pub fn layer_name(mut self, layer_name: impl Into<String>) -> Self {
+        self.layer_name = layer_name.into();
+        self
+    }
+
+    /// Sets the coordinate reference system in which the bands are expressed.
+    pub fn coordinates(mut self, coordinates: MvtCoordinates) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Encodes the given bands as a MVT tile (containing one layer) in protobuf format.
+    /// The bands that don't intersect the tile are skipped.
+    pub fn encode(&self, bands: &[Band]) -> Vec<u8> {
+        let mut values: Vec<f64> = Vec::new();
+        let mut value_indexes: FxHashMap<u64, u32> = FxHashMap::default();
+        let mut value_index = |v: f64| {
+            *value_indexes.entry(v.to_bits()).or_insert_with(|| {
+                values.push(v);
+                values.len() as u32 - 1
+            })
+        };
+
+        let mut layer = Vec::new();
+        write_varint_field(&mut layer, 15, 2);
+        write_bytes_field(&mut layer, 1, self.layer_name.as_bytes());
+
+        for band in bands {
+            let mut geometry = Vec::new();
+            let mut cursor = (0, 0);
+            for polygon in band.geometry().0.iter() {
+                self.encode_polygon(polygon, &mut cursor, &mut geometry);
+            }
+            if geometry.is_empty() {
+                continue;
+            }
+
+            let tags = [0, value_index(band.min_v), 1, value_index(band.max_v)];
+            let mut feature = Vec::new();
+            write_packed_field(&mut feature, 2, &tags);
+            write_varint_field(&mut feature, 3, POLYGON);
+            write_packed_field(&mut feature, 4, &geometry);
+            write_bytes_field(&mut layer, 2, &feature);
+        }
+
+        write_bytes_field(&mut layer, 3, b"min_v");
+        write_bytes_field(&mut layer, 3, b"max_v");
+        for v in values {
+            let mut value = Vec::new();
+            write_tag(&mut value, 3, 1);
+            value.extend_from_slice(&v.to_le_bytes());
+            write_bytes_field(&mut layer, 4, &value);
+        }
+        write_varint_field(&mut layer, 5, self.extent as u64);
+
+        let mut tile = Vec::new();
+        write_bytes_field(&mut tile, 3, &layer);
+        tile
+    }
+
+    /// Convert the coordinates of a point to the (non-quantized) tile grid.
+    fn to_tile(&self, coord: &Coord<f64>) -> Coord<f64> {
+        let (x, y) = match self.coordinates {
+            MvtCoordinates::WebMercator => (coord.x, coord.y),
+            MvtCoordinates::LonLat => (
+                coord.x * HALF_CIRCUMFERENCE / 180.,
+                // The latitudes are clamped, the poles being at infinity
+                (std::f64::consts::FRAC_PI_4
+                    + coord.y.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians() / 2.)
+                    .tan()
+                    .ln()
+                    * HALF_CIRCUMFERENCE
+                    / std::f64::consts::PI,
+            ),
+        };
+        let tile_size = 2. * HALF_CIRCUMFERENCE / (1u64 << self.z) as f64;
+        let extent = self.extent as f64;
+        Coord {
+            x: ((x + HALF_CIRCUMFERENCE) / tile_size - self.x as f64) * extent,
+            y: ((HALF_CIRCUMFERENCE - y) / tile_size - self.y as f64) * extent,
+        }
+    }
+
+    /// Project, clip and quantize a ring,
+    /// returning `None` if nothing remains of it in the tile.
+    fn tile_ring(&self, ring: &LineString<f64>) -> Option<Vec<Coord<f64>>> {
+        let points = ring
+            .0
+            .iter()
+            .map(|coord| self.to_tile(coord))
+            .collect::<Vec<_>>();
+        let (min, max) = (
+            -(self.buffer as f64),
+            self.extent as f64 + self.buffer as f64,
+        );
+        let mut ring = clip_ring(&points, [min, min, max, max])
+            .into_iter()
+            .map(|p| Coord {
+                x: p.x.round(),
+                y: p.y.round(),
+            })
+            .collect::<Vec<_>>();
+        ring.dedup();
+        while ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+            ring.pop();
+        }
+        if ring.len() < 3 || area(&ring) == 0. {
+            None
+        } else {
+            Some(ring)
+        }
+    }
+
+    /// Encode the rings of a polygon (exterior ring clockwise and holes
+    /// counter-clockwise, in tile coordinates whose y axis points down) as MVT commands.
+    fn encode_polygon(
+        &self,
+        polygon: &Polygon<f64>,
+        cursor: &mut (i64, i64),
+        geometry: &mut Vec<u32>,
+    ) {
+        let exterior = match self.tile_ring(polygon.exterior()) {
+            Some(ring) => ring,
+            None => return,
+        };
+        let rings = std::iter::once((exterior, true)).chain(
+            polygon
+                .interiors()
+                .iter()
+                .filter_map(|ring| self.tile_ring(ring))
+                .map(|ring| (ring, false)),
+        );
+
+        for (mut ring, is_exterior) in rings {
+            // The y axis of the tile points down: the exterior rings (clockwise) have a negative area
+            if (area(&ring) < 0.) != is_exterior {
+                ring.reverse();
+            }
+            geometry.push(command(MOVE_TO, 1));
+            push_point(geometry, cursor, ring[0]);
+            geometry.push(command(LINE_TO, ring.len() as u32 - 1));
+            for &point in &ring[1..] {
+                push_point(geometry, cursor, point);
+            }
+            geometry.push(command(CLOSE_PATH, 1));
+        }
+    }
+}
+
+#[inline]
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+#[inline]
+fn zigzag(v: i64) -> u32 {
+    ((v << 1) ^ (v >> 63)) as u32
+}
+
+#[inline]
+fn push_point(geometry: &mut Vec<u32>, cursor: &mut (i64, i64), point: Coord<f64>) {
+    let point = (point.x as i64, point.y as i64);
+    geometry.push(zigzag(point.0 - cursor.0));
+    geometry.push(zigzag(point.1 - cursor.1));
+    *cursor = point;
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8 & 0x7f) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[inline]
+fn write_tag(buf: &mut Vec<u8>, field: u32, wire_type: u8) {
+    write_varint(buf, ((field as u64) << 3) | wire_type as u64);
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u32, v: u64) {
+    write_tag(buf, field, 0);
+    write_varint(buf, v);
+}
+
+fn write_bytes_field(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_tag(buf, field, 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed_field(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len());
+    for &v in values {
+        write_varint(&mut packed, v as u64);
+    }
+    write_bytes_field(buf, field, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{LineString, MultiPolygon};
+
+    fn square(min: f64, max: f64) -> LineString<f64> {
+        LineString::from(vec![
+            (min, min),
+            (min, max),
+            (max, max),
+            (max, min),
+            (min, min),
+        ])
+    }
+
+    #[test]
+    fn clip_and_quantize_ring() {
+        // Tile 0/0/0 with an extent of 256: one unit in Web Mercator
+        // is 256 / (2 * HALF_CIRCUMFERENCE) units of the tile grid
+        let tile = MvtTile::new(0, 0, 0).unwrap().extent(256).buffer(8);
+        let ring = tile.tile_ring(&square(-1e9, 1e9)).unwrap();
+        assert_eq!(ring.len(), 4);
+        assert!(ring
+            .iter()
+            .all(|p| [-8., 264.].contains(&p.x) && [-8., 264.].contains(&p.y)));
+
+        let ring = tile
+            .tile_ring(&square(0., HALF_CIRCUMFERENCE / 2.))
+            .unwrap();
+        let mut sorted = ring
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>();
+        sorted.sort();
+        assert_eq!(sorted, vec![(128, 64), (128, 128), (192, 64), (192, 128)]);
+
+        // Rings that are outside of the tile (or too small) are dropped
+        assert!(tile.tile_ring(&square(1e9, 2e9)).is_none());
+        assert!(tile.tile_ring(&square(0., 1.)).is_none());
+    }
+
+    #[test]
+    fn clip_ring_reaching_poles() {
+        let tile = MvtTile::new(0, 0, 0)
+            .unwrap()
+            .extent(256)
+            .buffer(0)
+            .coordinates(MvtCoordinates::LonLat);
+        let ring = LineString::from(vec![
+            (-180., -90.),
+            (-180., 90.),
+            (180., 90.),
+            (180., -90.),
+            (-180., -90.),
+        ]);
+        let ring = tile.tile_ring(&ring).unwrap();
+        let mut sorted = ring
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>();
+        sorted.sort();
+        assert_eq!(sorted, vec![(0, 0), (0, 256), (256, 0), (256, 256)]);
+    }
+
+    #[test]
+    fn tile_coordinates() {
+        assert!(MvtTile::new(32, u32::MAX, 0).is_ok());
+        assert!(matches!(
+            MvtTile::new(33, 0, 0).err().unwrap().kind(),
+            ErrorKind::BadTile
+        ));
+        assert!(MvtTile::new(64, 0, 0).is_err());
+        assert!(MvtTile::new(2, 4, 0).is_err());
+        assert!(MvtTile::new(2, 3, 4).is_err());
+    }
+
+    #[test]
+    fn encode_polygon_commands_and_winding() {
+        let tile = MvtTile::new(0, 0, 0).unwrap().extent(256);
+        let polygon = Polygon::new(
+            square(0., HALF_CIRCUMFERENCE / 2.),
+            vec![square(HALF_CIRCUMFERENCE / 8., HALF_CIRCUMFERENCE / 4.)],
+        );
+        let mut geometry = Vec::new();
+        tile.encode_polygon(&polygon, &mut (0, 0), &mut geometry);
+
+        // MoveTo(1), 2 params, LineTo(3), 6 params, ClosePath(1), for each ring
+        assert_eq!(geometry.len(), 2 * 11);
+        assert_eq!(geometry[0], command(MOVE_TO, 1));
+        assert_eq!(geometry[3], command(LINE_TO, 3));
+        assert_eq!(geometry[10], command(CLOSE_PATH, 1));
+
+        // Decode the rings to check their winding order
+        let mut cursor = (0i64, 0i64);
+        let mut areas = Vec::new();
+        for ring_commands in geometry.chunks(11) {
+            let params = ring_commands
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| ![0, 3, 10].contains(i))
+                .map(|(_, &v)| ((v >> 1) as i64) ^ -((v & 1) as i64))
+                .collect::<Vec<_>>();
+            let ring = params
+                .chunks(2)
+                .map(|d| {
+                    cursor = (cursor.0 + d[0], cursor.1 + d[1]);
+                    Coord {
+                        x: cursor.0 as f64,
+                        y: cursor.1 as f64,
+                    }
+                })
+                .collect::<Vec<_>>();
+            areas.push(area(&ring));
+        }
+        assert!(areas[0] < 0.);
+        assert!(areas[1] > 0.);
+    }
+
+    #[test]
+    fn encode_tile() {
+        let bands = vec![
+            Band {
+                min_v: 1.,
+                max_v: 2.,
+                geometry: MultiPolygon(vec![Polygon::new(square(-10., 10.), vec![])]),
+            },
+            Band {
+                min_v: 2.,
+                max_v: 3.,
+                geometry: MultiPolygon(vec![Polygon::new(square(50., 60.), vec![])]),
+            },
+        ];
+        let encoded = MvtTile::new(0, 0, 0)
+            .unwrap()
+            .coordinates(MvtCoordinates::LonLat)
+            .layer_name("contours")
+            .encode(&bands);
+
+        // A tile message with a single layer (field 3, length-delimited)
+        assert_eq!(encoded[0], 0x1a);
+        let mut layer_len = 0;
+        let mut i = 1;
+        let mut shift = 0;
+        loop {
+            layer_len |= ((encoded[i] & 0x7f) as usize) << shift;
+            shift += 7;
+            i += 1;
+            if encoded[i - 1] & 0x80 == 0 {
+                break;
+            }
+        }
+        assert_eq!(encoded.len(), i + layer_len);
+        // Layer version 2, then its name
+        assert_eq!(&encoded[i..i + 4], &[0x78, 0x02, 0x0a, 0x08]);
+        assert_eq!(&encoded[i + 4..i + 12], b"contours");
+        // Two features, two keys, three distinct values
+        let count = |needle: &[u8]| {
+            encoded
+                .windows(needle.len())
+                .filter(|w| *w == needle)
+                .count()
+        };
+        assert_eq!(count(b"min_v"), 1);
+        assert_eq!(count(b"max_v"), 1);
+        assert_eq!(count(&[0x12, 0x04, 0x00, 0x00, 0x01, 0x01]), 1);
+        assert_eq!(count(&[0x12, 0x04, 0x00, 0x01, 0x01, 0x02]), 1);
+        assert_eq!(count(&[0x22, 0x09, 0x19]), 3);
+    }
+}
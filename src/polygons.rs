@@ -1,45 +1,34 @@
 use crate::errors::{new_error, ErrorKind, Result};
-use crate::grid::BorrowedGrid;
-use crate::isobands::{Cell, EnterType, Pt, Settings};
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::isobands::{Cell, Edge, EnterType, NoDataBoundary, Pt, Settings};
+use crate::shape_coordinates::prepare_cell;
 use geo_types::Point;
 
-fn require_frame(data: &BorrowedGrid<f64>, lowerbound: f64, upperbound: f64) -> bool {
-    let mut frame_required: bool = true;
+pub(crate) fn require_frame<T: GridValue>(data: &BorrowedGrid<T>, opt: &Settings) -> bool {
     let rows = data.height();
     let cols = data.width();
 
-    for row in data.iter_rows() {
-        if row[0] < lowerbound
-            || row[0] > upperbound
-            || row[cols - 1] < lowerbound
-            || row[cols - 1] > upperbound
-        {
-            frame_required = false;
-            break;
+    // Missing values only break the frame when the bands are cut half-way to them
+    let in_band = |p| {
+        let v = data.value(p);
+        if v.is_nan() {
+            opt.nodata_boundary == NoDataBoundary::DropCell
+        } else {
+            v >= opt.min_v && v <= opt.max_v
         }
-    }
+    };
 
-    if frame_required && data[(cols - 1, 0)] < lowerbound
-        || data[(cols - 1, 0)] > upperbound
-        || data[(cols - 1, rows - 1)] < lowerbound
-        || data[(cols - 1, rows - 1)] > upperbound
-    {
-        frame_required = false;
-    }
+    (0..rows).all(|j| in_band((0, j)) && in_band((cols - 1, j)))
+        && (0..cols).all(|i| in_band((i, 0)) && in_band((i, rows - 1)))
+}
 
-    if frame_required {
-        for i in 0..cols {
-            if data[(i, 0)] < lowerbound
-                || data[(i, 0)] > upperbound
-                || data[(i, rows - 1)] < lowerbound
-                || data[(i, rows - 1)] > upperbound
-            {
-                frame_required = false;
-                break;
-            }
-        }
-    }
-    frame_required
+/// Whether the cell `(x, y)` is dropped because it has a missing corner
+/// (see [`NoDataBoundary::DropCell`]).
+fn dropped_cell<T: GridValue>(data: &BorrowedGrid<T>, x: usize, y: usize, opt: &Settings) -> bool {
+    opt.nodata_boundary == NoDataBoundary::DropCell
+        && [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+            .into_iter()
+            .any(|p| data.value(p).is_nan())
 }
 
 fn entry_coordinate(x: i32, y: i32, mode: usize, path: &[Pt]) -> Point<f64> {
@@ -95,16 +84,84 @@ fn entry_dir(e: &EnterType) -> usize {
 }
 
 macro_rules! check_out_of_grid {
-    ($x:ident, $y:ident, $cell_grid:ident) => {
-        usize::try_from($x).is_err()
-            || usize::try_from($y).is_err()
-            || $cell_grid.get($x as usize).is_none()
-            || $cell_grid[$x as usize].get($y as usize).is_none()
+    ($x:ident, $y:ident, $cols:ident, $rows:ident) => {
+        $x < 0 || $y < 0 || $x >= $cols as i32 || $y >= $rows as i32
     };
 }
 
-pub(crate) fn trace_band_paths(
-    data: &BorrowedGrid<f64>,
+/// The ring following the frame of a grid of `cols` x `rows` cells,
+/// added when all the values on the frame are inside the band.
+pub(crate) fn frame_ring(cols: usize, rows: usize) -> Vec<Point<f64>> {
+    vec![
+        Point::new(0., 0.),
+        Point::new(0., rows as f64),
+        Point::new(cols as f64, rows as f64),
+        Point::new(cols as f64, 0.),
+        Point::new(0., 0.),
+    ]
+}
+
+/// The order in which the edges of a cell are used to start a ring.
+pub(crate) const START_EDGES: [EnterType; 8] = [
+    EnterType::BL,
+    EnterType::LB,
+    EnterType::LT,
+    EnterType::TL,
+    EnterType::TR,
+    EnterType::RT,
+    EnterType::RB,
+    EnterType::BR,
+];
+
+/// The cells of a band prepared on demand, when only some of its rings are traced
+/// (the other cells of the cell grid being left to `None`).
+#[derive(Default)]
+pub(crate) struct LazyCells {
+    /// The cells prepared so far
+    pub(crate) prepared: Vec<(usize, usize)>,
+    /// The edges removed from the cells by the rings traced so far
+    pub(crate) removed: Vec<(usize, usize, EnterType, Edge)>,
+}
+
+impl LazyCells {
+    /// Prepares the cell `(x, y)` if it isn't prepared yet.
+    pub(crate) fn prepare<T: GridValue>(
+        &mut self,
+        data: &BorrowedGrid<T>,
+        cell_grid: &mut [Vec<Option<Cell>>],
+        x: usize,
+        y: usize,
+        opt: &Settings,
+    ) -> Result<()> {
+        if cell_grid[x][y].is_none() {
+            cell_grid[x][y] = prepare_cell(x, y, data, opt)?;
+            if cell_grid[x][y].is_some() {
+                self.prepared.push((x, y));
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts back the removed edges in their cells.
+    pub(crate) fn restore_edges(&mut self, cell_grid: &mut [Vec<Option<Cell>>]) {
+        for (x, y, enter, edge) in self.removed.drain(..) {
+            if let Some(cell) = cell_grid[x][y].as_mut() {
+                cell.edges.insert(enter, edge);
+            }
+        }
+    }
+
+    /// Resets the prepared cells to `None`.
+    pub(crate) fn clear(&mut self, cell_grid: &mut [Vec<Option<Cell>>]) {
+        for (x, y) in self.prepared.drain(..) {
+            cell_grid[x][y] = None;
+        }
+        self.removed.clear();
+    }
+}
+
+pub(crate) fn trace_band_paths<T: GridValue>(
+    data: &BorrowedGrid<T>,
     cell_grid: &mut [Vec<Option<Cell>>],
     opt: &Settings,
 ) -> Result<Vec<Vec<Point<f64>>>> {
@@ -112,19 +169,71 @@ pub(crate) fn trace_band_paths(
     let rows = data.height() - 1;
     let cols = data.width() - 1;
 
-    let available_starts = [
-        EnterType::BL,
-        EnterType::LB,
-        EnterType::LT,
-        EnterType::TL,
-        EnterType::TR,
-        EnterType::RT,
-        EnterType::RB,
-        EnterType::BR,
-    ];
+    let has_frame = require_frame(data, opt);
+    if has_frame {
+        polygons.push(frame_ring(cols, rows));
+    }
+    // Whether a ring went along the frame of the grid (around dropped cells)
+    let mut walked_frame = false;
+
+    for i in 0..cell_grid.len() {
+        for j in 0..cell_grid[i].len() {
+            let temp_cell_grid = &cell_grid[i][j];
+            if temp_cell_grid.is_none() || temp_cell_grid.as_ref().unwrap().edges.is_empty() {
+                continue;
+            }
+            for nextedge in &START_EDGES {
+                let cg = cell_grid[i][j].as_ref().unwrap();
+                if cg.edges.contains_key(nextedge) {
+                    polygons.push(trace_ring(
+                        data,
+                        cell_grid,
+                        (i, j),
+                        nextedge,
+                        opt,
+                        &mut walked_frame,
+                        None,
+                    )?);
+                }
+            }
+        }
+    }
+
+    // The frame is then already part of these rings
+    if has_frame && walked_frame {
+        polygons.remove(0);
+    }
+
+    Ok(polygons)
+}
+
+/// Traces the ring starting with the edge `nextedge` of the cell `(i, j)`,
+/// removing its edges from the cells.
+///
+/// With `lazy`, the cells are prepared when the ring goes through them
+/// and the removed edges are kept.
+pub(crate) fn trace_ring<T: GridValue>(
+    data: &BorrowedGrid<T>,
+    cell_grid: &mut [Vec<Option<Cell>>],
+    (i, j): (usize, usize),
+    nextedge: &EnterType,
+    opt: &Settings,
+    walked_frame: &mut bool,
+    mut lazy: Option<&mut LazyCells>,
+) -> Result<Vec<Point<f64>>> {
+    let rows = data.height() - 1;
+    let cols = data.width() - 1;
 
     let add_x = [0, -1, 0, 1];
     let add_y = [-1, 0, 1, 0];
+    /* direction of the invalid cells, while walking along them */
+    let out_x = [1, 0, -1, 0];
+    let out_y = [0, -1, 0, 1];
+    let max_walk = 4 * cols * rows;
+
+    let valid_cell = |x: i32, y: i32| {
+        !check_out_of_grid!(x, y, cols, rows) && !dropped_cell(data, x as usize, y as usize, opt)
+    };
 
     let valid_entries = [
         [EnterType::RT, EnterType::RB], /* down */
@@ -133,160 +242,134 @@ pub(crate) fn trace_band_paths(
         [EnterType::TL, EnterType::TR], /* right */
     ];
 
-    if require_frame(data, opt.min_v, opt.max_v) {
-        polygons.push(vec![
-            Point::new(0., 0.),
-            Point::new(0., rows as f64),
-            Point::new(cols as f64, rows as f64),
-            Point::new(cols as f64, 0.),
-            Point::new(0., 0.),
-        ]);
-    }
+    let edge = &cell_grid[i][j].as_ref().unwrap().edges[nextedge];
+    let mut path = Vec::new();
+    let mut enter = nextedge.clone();
 
-    for i in 0..cell_grid.len() {
-        for j in 0..cell_grid[i].len() {
-            let temp_cell_grid = &cell_grid[i][j];
-            if temp_cell_grid.is_none() || temp_cell_grid.as_ref().unwrap().edges.is_empty() {
-                continue;
+    let mut x = i as i32;
+    let mut y = j as i32;
+    let mut finalized = false;
+    let origin = Point::new(i as f64 + edge.path[0].0, j as f64 + edge.path[0].1);
+
+    path.push(origin);
+
+    /* start traceback */
+    while !finalized {
+        // We are being extra careful here,
+        // but I think we can remove this check
+        if check_out_of_grid!(x, y, cols, rows) {
+            return Err(new_error(ErrorKind::OutOfBounds));
+        }
+
+        if let Some(lazy) = lazy.as_mut() {
+            lazy.prepare(data, cell_grid, x as usize, y as usize, opt)?;
+        }
+        // We know that cc is not None here, so we can unwrap it directly
+        let cc = cell_grid[x as usize][y as usize].as_mut().unwrap();
+
+        /* remove edge from cell */
+        let mut _ee = cc.edges.remove(&enter);
+        if _ee.is_none() {
+            break;
+        }
+        let ee = _ee.unwrap();
+
+        /* add last point of edge to path array, since we extend a polygon */
+        if let Some(via) = &ee.via {
+            path.push(Point::new(via.0 + x as f64, via.1 + y as f64));
+        }
+        let point = Point::new(ee.path[1].0 + x as f64, ee.path[1].1 + y as f64);
+        path.push(point);
+
+        let (dx, dy) = (ee.move_info.x, ee.move_info.y);
+        let next_enter = ee.move_info.enter.clone();
+        if let Some(lazy) = lazy.as_mut() {
+            lazy.removed.push((x as usize, y as usize, enter, ee));
+        }
+        enter = next_enter;
+        x += dx;
+        y += dy;
+
+        /* handle moves out of the grid or into a dropped cell */
+        if !valid_cell(x, y) {
+            x -= dx;
+            y -= dy;
+            let mut dir = match (dx, dy) {
+                (1, 0) => 0,  /* move downwards */
+                (-1, 0) => 2, /* move upwards */
+                (0, 1) => 3,  /* move right */
+                (0, -1) => 1, /* move left */
+                _ => return Err(new_error(ErrorKind::UnexpectedOutOfGridMove)),
+            };
+
+            if x == i as i32 && y == j as i32 && dir == entry_dir(nextedge) {
+                // finalized = true;
+                // enter = nextedge.clone();
+                break;
             }
-            for nextedge in &available_starts {
-                //if let Some(cg) = &cell_grid[i][j] {
-                let cg = cell_grid[i][j].as_ref().unwrap();
-                if let Some(edge) = cg.edges.get(nextedge) {
-                    let mut path = Vec::new();
-                    let mut enter = nextedge.clone();
-
-                    let mut x = i as i32;
-                    let mut y = j as i32;
-                    let mut finalized = false;
-                    let origin = Point::new(i as f64 + edge.path[0].0, j as f64 + edge.path[0].1);
-
-                    path.push(origin);
-
-                    /* start traceback */
-                    while !finalized {
-                        // We are being extra careful here,
-                        // but I think we can remove this check
-                        if check_out_of_grid!(x, y, cell_grid) {
-                            return Err(new_error(ErrorKind::OutOfBounds));
-                        }
 
-                        // We know that cc is not None here, so we can unwrap it directly
-                        let mut cc = cell_grid[x as usize][y as usize].as_mut().unwrap();
+            // Walk along the boundary between the valid cells (on the right)
+            // and the cells outside of the grid or dropped (on the left)
+            let mut count = 0;
+            loop {
+                let mut found_entry = false;
+                if count > max_walk {
+                    // println!("Boundary walk overflow! This should never happen!");
+                    break;
+                }
 
-                        /* remove edge from cell */
-                        let mut _ee = cc.edges.remove(&enter);
-                        if _ee.is_none() {
+                if let Some(lazy) = lazy.as_mut() {
+                    lazy.prepare(data, cell_grid, x as usize, y as usize, opt)?;
+                }
+                if let Some(cc) = cell_grid[x as usize][y as usize].as_mut() {
+                    /* check for re-entry */
+                    for ve in valid_entries[dir].iter() {
+                        if let Some(ee) = cc.edges.get(ve) {
+                            /* found re-entry */
+                            path.push(entry_coordinate(x, y, dir, &ee.path));
+                            enter = ve.clone();
+                            found_entry = true;
                             break;
                         }
-                        let mut ee = &_ee.unwrap();
-
-                        /* add last point of edge to path array, since we extend a polygon */
-                        let point = Point::new(ee.path[1].0 + x as f64, ee.path[1].1 + y as f64);
-                        path.push(point);
-
-                        enter = ee.move_info.enter.clone();
-                        x += ee.move_info.x;
-                        y += ee.move_info.y;
-
-                        /* handle out-of-grid moves */
-                        if check_out_of_grid!(x, y, cell_grid) {
-                            let mut dir;
-                            let mut count = 0;
-
-                            if x == cols as i32 {
-                                x -= 1;
-                                dir = 0; /* move downwards */
-                            } else if x < 0 {
-                                x += 1;
-                                dir = 2; /* move upwards */
-                            } else if y == rows as i32 {
-                                y -= 1;
-                                dir = 3; /* move right */
-                            } else if y < 0 {
-                                y += 1;
-                                dir = 1; /* move left */
-                            } else {
-                                return Err(new_error(ErrorKind::UnexpectedOutOfGridMove));
-                            }
-
-                            if x == i as i32 && y == j as i32 && dir == entry_dir(nextedge) {
-                                // finalized = true;
-                                // enter = nextedge.clone();
-                                break;
-                            }
-
-                            loop {
-                                let mut found_entry = false;
-                                if count > 4 {
-                                    // println!("Direction change counter overflow! This should never happen!");
-                                    break;
-                                }
-
-                                if usize::try_from(x).is_ok()
-                                    && usize::try_from(y).is_ok()
-                                    && cell_grid.get(x as usize).is_some()
-                                    && cell_grid[x as usize].get(y as usize).is_some()
-                                    && cell_grid[x as usize][y as usize].is_some()
-                                {
-                                    cc = cell_grid[x as usize][y as usize].as_mut().unwrap();
-
-                                    /* check for re-entry */
-                                    for s in 0..valid_entries[dir].len() {
-                                        let ve = &valid_entries[dir][s];
-                                        if cc.edges.contains_key(ve) {
-                                            /* found re-entry */
-                                            ee = cc.edges.get(ve).unwrap();
-                                            path.push(entry_coordinate(x, y, dir, &ee.path));
-                                            enter = ve.clone();
-                                            found_entry = true;
-                                            break;
-                                        }
-                                    }
-                                }
-
-                                if found_entry {
-                                    break;
-                                } else {
-                                    path.push(skip_coordinate(x, y, dir));
-                                    x += add_x[dir];
-                                    y += add_y[dir];
-
-                                    /* change direction if we moved out of grid again */
-                                    if check_out_of_grid!(x, y, cell_grid)
-                                        && (((dir == 0) && (y < 0))
-                                            || ((dir == 1) && (x < 0))
-                                            || ((dir == 2) && (y == rows as i32))
-                                            || ((dir == 3) && (x == cols as i32)))
-                                    {
-                                        x -= add_x[dir];
-                                        y -= add_y[dir];
-
-                                        dir = (dir + 1) % 4;
-                                        count += 1;
-                                    }
-
-                                    if x == i as i32 && y == j as i32 && dir == entry_dir(nextedge)
-                                    {
-                                        finalized = true;
-                                        enter = nextedge.clone();
-                                        break;
-                                    }
-                                }
-                            }
-                        }
                     }
+                }
 
-                    if path[path.len() - 1].x_y() != origin.x_y() {
-                        path.push(origin);
+                if found_entry {
+                    break;
+                } else {
+                    let (nx, ny) = (x + out_x[dir], y + out_y[dir]);
+                    *walked_frame |= check_out_of_grid!(nx, ny, cols, rows);
+                    path.push(skip_coordinate(x, y, dir));
+                    let (ax, ay) = (x + add_x[dir], y + add_y[dir]);
+                    let (ox, oy) = (ax + out_x[dir], ay + out_y[dir]);
+
+                    if !valid_cell(ax, ay) {
+                        /* turn around the corner of the current cell */
+                        dir = (dir + 1) % 4;
+                    } else if valid_cell(ox, oy) {
+                        /* turn around the corner of the invalid cell */
+                        x = ox;
+                        y = oy;
+                        dir = (dir + 3) % 4;
+                    } else {
+                        x = ax;
+                        y = ay;
                     }
+                    count += 1;
 
-                    polygons.push(path);
+                    if x == i as i32 && y == j as i32 && dir == entry_dir(nextedge) {
+                        finalized = true;
+                        enter = nextedge.clone();
+                        break;
+                    }
                 }
-                // }
             }
         }
     }
 
-    Ok(polygons)
+    if path[path.len() - 1].x_y() != origin.x_y() {
+        path.push(origin);
+    }
+
+    Ok(path)
 }
@@ -0,0 +1,63 @@
+use crate::errors::Result;
+use crate::grid::GridValue;
+use crate::isobands::{trace_bands, Band, BandRaw, Cell, ContourBuilder};
+use crate::quadtree::QuadTree;
+use crate::utils::empty_cell_grid;
+use std::borrow::Cow;
+
+/// A grid prepared for computing the contours several times with different thresholds
+/// (e.g. when the thresholds are chosen interactively), created by [`ContourBuilder::prepare`].
+///
+/// The quadtree (when the `use_quad_tree` option is set) is built only once,
+/// and the cells used while tracing the bands are kept between the calls.
+pub struct PreparedGrid<'a, T: GridValue> {
+    builder: &'a ContourBuilder,
+    data: Cow<'a, [T]>,
+    tree: Option<QuadTree>,
+    cell_grid: Vec<Vec<Option<Cell>>>,
+}
+
+impl<'a, T: GridValue> PreparedGrid<'a, T> {
+    pub(crate) fn new(builder: &'a ContourBuilder, data: Cow<'a, [T]>) -> Result<Self> {
+        let grid = builder.grid(&data)?;
+        let tree = if builder.use_quad_tree {
+            Some(QuadTree::new(&grid))
+        } else {
+            None
+        };
+        let cell_grid = empty_cell_grid(builder.width, builder.height);
+
+        Ok(PreparedGrid {
+            builder,
+            data,
+            tree,
+            cell_grid,
+        })
+    }
+
+    /// The values of the grid.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Generates contour MultiPolygons for the given thresholds
+    /// (see [`ContourBuilder::contours`]).
+    pub fn contours(&mut self, thresholds: &[f64]) -> Result<Vec<Band>> {
+        let bands = self.isobands(thresholds)?;
+        self.builder.bands_from_raw(bands)
+    }
+
+    /// Generates the rings of the bands for the given thresholds, in grid coordinates
+    /// (i.e. before the transformation of the coordinates, see [`BandRaw`]).
+    pub fn isobands(&mut self, thresholds: &[f64]) -> Result<Vec<BandRaw>> {
+        let grid = self.builder.grid(&self.data)?;
+        let mut bands = trace_bands(
+            &grid,
+            &self.builder.band_settings(thresholds)?,
+            self.tree.as_ref(),
+            &mut self.cell_grid,
+        )?;
+        self.builder.post_process(&mut bands);
+        Ok(bands)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::grid::{BorrowedGrid, GridCoord};
+use crate::grid::{BorrowedGrid, GridCoord, GridValue};
 
 #[derive(Debug)]
 pub(crate) struct TreeNode {
@@ -13,7 +13,13 @@ pub(crate) struct TreeNode {
 }
 
 impl TreeNode {
-    pub fn new(data: &BorrowedGrid<f64>, x: usize, y: usize, dx: usize, dy: usize) -> TreeNode {
+    pub fn new<T: GridValue>(
+        data: &BorrowedGrid<T>,
+        x: usize,
+        y: usize,
+        dx: usize,
+        dy: usize,
+    ) -> TreeNode {
         let mut dx_tmp = dx;
         let mut dy_tmp = dy;
         let mut msb_x = 0;
@@ -35,10 +41,21 @@ impl TreeNode {
             //     data[(y, x)].min(data[(y + 1, x)].min(data[(y, x + 1)].min(data[(y + 1, x + 1)])));
             // tn.upper_bound =
             //     data[(y, x)].max(data[(y + 1, x)].max(data[(y, x + 1)].max(data[(y + 1, x + 1)])));
-            tn.lower_bound =
-                data[(x, y)].min(data[(x + 1, y)].min(data[(x, y + 1)].min(data[(x + 1, y + 1)])));
-            tn.upper_bound =
-                data[(x, y)].max(data[(x + 1, y)].max(data[(x, y + 1)].max(data[(x + 1, y + 1)])));
+            let (v0, v1, v2, v3) = (
+                data.value((x, y)),
+                data.value((x + 1, y)),
+                data.value((x, y + 1)),
+                data.value((x + 1, y + 1)),
+            );
+            if v0.is_nan() || v1.is_nan() || v2.is_nan() || v3.is_nan() {
+                // Cells with missing values always need to be visited
+                // (their shape also depends on the values around the missing ones)
+                tn.lower_bound = f64::NEG_INFINITY;
+                tn.upper_bound = f64::INFINITY;
+            } else {
+                tn.lower_bound = v0.min(v1.min(v2.min(v3)));
+                tn.upper_bound = v0.max(v1.max(v2.max(v3)));
+            }
         } else {
             // Get most significant bit from dx
             if dx > 1 {
@@ -122,37 +139,17 @@ impl TreeNode {
             cells.push((self.x, self.y));
             cells
         } else {
-            if self.child_a.is_some() {
-                cells.extend(
-                    self.child_a
-                        .as_ref()
-                        .unwrap()
-                        .cells_in_band(lowerbound, upperbound),
-                );
+            if let Some(child) = &self.child_a {
+                cells.extend(child.cells_in_band(lowerbound, upperbound));
             }
-            if self.child_b.is_some() {
-                cells.extend(
-                    self.child_b
-                        .as_ref()
-                        .unwrap()
-                        .cells_in_band(lowerbound, upperbound),
-                );
+            if let Some(child) = &self.child_b {
+                cells.extend(child.cells_in_band(lowerbound, upperbound));
             }
-            if self.child_c.is_some() {
-                cells.extend(
-                    self.child_c
-                        .as_ref()
-                        .unwrap()
-                        .cells_in_band(lowerbound, upperbound),
-                );
+            if let Some(child) = &self.child_c {
+                cells.extend(child.cells_in_band(lowerbound, upperbound));
             }
-            if self.child_d.is_some() {
-                cells.extend(
-                    self.child_d
-                        .as_ref()
-                        .unwrap()
-                        .cells_in_band(lowerbound, upperbound),
-                );
+            if let Some(child) = &self.child_d {
+                cells.extend(child.cells_in_band(lowerbound, upperbound));
             }
             cells
         }
@@ -165,7 +162,7 @@ pub(crate) struct QuadTree {
 }
 
 impl QuadTree {
-    pub fn new(data: &BorrowedGrid<f64>) -> QuadTree {
+    pub fn new<T: GridValue>(data: &BorrowedGrid<T>) -> QuadTree {
         QuadTree {
             root: TreeNode::new(data, 0, 0, data.width() - 1, data.height() - 1),
         }
@@ -1,56 +1,61 @@
 use crate::errors::{new_error, ErrorKind, Result};
-use crate::grid::BorrowedGrid;
-use crate::isobands::{Cell, Edge, EnterType, MoveInfo, Pt, Settings};
+use crate::grid::{BorrowedGrid, GridCoord, GridValue};
+use crate::isobands::{
+    Cell, Edge, EnterType, Interpolation, MoveInfo, NoDataBoundary, Pt, Settings,
+};
 use rustc_hash::FxHashMap;
 // use lazy_static::lazy_static;
 // use rustc_hash::FxHasher;
 // use std::collections::HashMap;
 // use std::hash::BuildHasherDefault;
 
-fn interpolate_linear_ab(a: f64, b: f64, v0: f64, v1: f64) -> f64 {
-    let (v0, v1) = if v0 > v1 { (v1, v0) } else { (v0, v1) };
+/// Position of the band boundary on an edge that has a missing value at one of its ends:
+/// such edges only exist with `NoDataBoundary::HalfCell` (the other cells being dropped),
+/// where the boundary is placed half-way between the valid and the missing value.
+const MISSING_POSITION: f64 = 0.5;
+
+fn interpolate_ab(a: f64, b: f64, opt: &Settings) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        return MISSING_POSITION;
+    }
+    let (v0, v1) = (opt.min_v, opt.max_v);
 
     if a < b {
         if a < v0 {
-            (v0 - a) / (b - a)
+            opt.interpolation.position(a, b, v0)
         } else {
-            (v1 - a) / (b - a)
+            opt.interpolation.position(a, b, v1)
         }
     } else if a > v1 {
-        (a - v1) / (a - b)
+        opt.interpolation.position(a, b, v1)
     } else {
-        (a - v0) / (a - b)
+        opt.interpolation.position(a, b, v0)
     }
 }
 
-fn interpolate_linear_a(a: f64, b: f64, min_v: f64, max_v: f64) -> f64 {
-    if a < b {
-        (min_v - a) / (b - a)
-    } else {
-        (a - max_v) / (a - b)
+fn interpolate_a(a: f64, b: f64, opt: &Settings) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        return MISSING_POSITION;
     }
-}
-
-fn interpolate_linear_b(a: f64, b: f64, min_v: f64, max_v: f64) -> f64 {
     if a < b {
-        (max_v - a) / (b - a)
+        opt.interpolation.position(a, b, opt.min_v)
     } else {
-        (a - min_v) / (a - b)
+        opt.interpolation.position(a, b, opt.max_v)
     }
 }
 
-fn compute_center_average(bl: f64, br: f64, tl: f64, tr: f64, min_v: f64, max_v: f64) -> u8 {
-    let average = (bl + br + tl + tr) / 4.;
-    if average > max_v {
-        2
-    } else if average < min_v {
-        0
+fn interpolate_b(a: f64, b: f64, opt: &Settings) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        return MISSING_POSITION;
+    }
+    if a < b {
+        opt.interpolation.position(a, b, opt.max_v)
     } else {
-        1
+        opt.interpolation.position(a, b, opt.min_v)
     }
 }
 
-/// Below are lookup for shapes, ported from https://github.com/RaumZeit/MarchingSquares.js/blob/master/src/isobands.js
+// Below are lookup for shapes, ported from https://github.com/RaumZeit/MarchingSquares.js/blob/master/src/isobands.js
 
 fn square(_cell: &mut Cell, _opt: &Settings) {
     // This is a no-op due to how we are tracing the polygons
@@ -65,13 +70,14 @@ fn square(_cell: &mut Cell, _opt: &Settings) {
 }
 
 fn triangle_bl(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -90,13 +96,14 @@ fn triangle_bl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn triangle_br(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -115,13 +122,14 @@ fn triangle_br(cell: &mut Cell, opt: &Settings) {
 }
 
 fn triangle_tr(cell: &mut Cell, opt: &Settings) {
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -140,13 +148,14 @@ fn triangle_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn triangle_tl(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
 
     cell.edges.insert(
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -165,13 +174,14 @@ fn triangle_tl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_t(cell: &mut Cell, opt: &Settings) {
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
 
     cell.edges.insert(
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -191,13 +201,14 @@ fn tetragon_t(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_r(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -217,13 +228,14 @@ fn tetragon_r(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_b(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -243,13 +255,14 @@ fn tetragon_b(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_l(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -269,15 +282,16 @@ fn tetragon_l(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_bl(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -290,6 +304,7 @@ fn tetragon_bl(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -309,15 +324,16 @@ fn tetragon_bl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_br(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -330,6 +346,7 @@ fn tetragon_br(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -349,15 +366,16 @@ fn tetragon_br(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_tr(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -370,6 +388,7 @@ fn tetragon_tr(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -389,15 +408,16 @@ fn tetragon_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_tl(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
 
     cell.edges.insert(
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -410,6 +430,7 @@ fn tetragon_tl(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -429,15 +450,16 @@ fn tetragon_tl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_lr(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -450,6 +472,7 @@ fn tetragon_lr(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -469,15 +492,16 @@ fn tetragon_lr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn tetragon_tb(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -490,6 +514,7 @@ fn tetragon_tb(cell: &mut Cell, opt: &Settings) {
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -509,13 +534,14 @@ fn tetragon_tb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_tr(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -536,13 +562,14 @@ fn pentagon_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_tl(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -563,13 +590,14 @@ fn pentagon_tl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_br(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -590,13 +618,14 @@ fn pentagon_br(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_bl(cell: &mut Cell, opt: &Settings) {
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -617,15 +646,16 @@ fn pentagon_bl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_tr_rl(cell: &mut Cell, opt: &Settings) {
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -638,6 +668,7 @@ fn pentagon_tr_rl(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -658,15 +689,16 @@ fn pentagon_tr_rl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_rb_bt(cell: &mut Cell, opt: &Settings) {
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -679,6 +711,7 @@ fn pentagon_rb_bt(cell: &mut Cell, opt: &Settings) {
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -699,15 +732,16 @@ fn pentagon_rb_bt(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_bl_lr(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -720,6 +754,7 @@ fn pentagon_bl_lr(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -740,15 +775,16 @@ fn pentagon_bl_lr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_lt_tb(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -761,6 +797,7 @@ fn pentagon_lt_tb(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -781,15 +818,16 @@ fn pentagon_lt_tb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_bl_tb(cell: &mut Cell, opt: &Settings) {
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -802,6 +840,7 @@ fn pentagon_bl_tb(cell: &mut Cell, opt: &Settings) {
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -822,15 +861,16 @@ fn pentagon_bl_tb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_lt_rl(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_ab(cell.x1, cell.x3, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -843,6 +883,7 @@ fn pentagon_lt_rl(cell: &mut Cell, opt: &Settings) {
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -863,15 +904,16 @@ fn pentagon_lt_rl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_tr_bt(cell: &mut Cell, opt: &Settings) {
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -884,6 +926,7 @@ fn pentagon_tr_bt(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -904,15 +947,16 @@ fn pentagon_tr_bt(cell: &mut Cell, opt: &Settings) {
 }
 
 fn pentagon_rb_lr(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -925,6 +969,7 @@ fn pentagon_rb_lr(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -945,15 +990,16 @@ fn pentagon_rb_lr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_lt_tr(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -966,6 +1012,7 @@ fn hexagon_lt_tr(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -987,15 +1034,16 @@ fn hexagon_lt_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_bl_lt(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1008,6 +1056,7 @@ fn hexagon_bl_lt(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1029,15 +1078,16 @@ fn hexagon_bl_lt(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_bl_rb(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1050,6 +1100,7 @@ fn hexagon_bl_rb(cell: &mut Cell, opt: &Settings) {
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1071,15 +1122,16 @@ fn hexagon_bl_rb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_tr_rb(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1092,6 +1144,7 @@ fn hexagon_tr_rb(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1113,15 +1166,16 @@ fn hexagon_tr_rb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_lt_rb(cell: &mut Cell, opt: &Settings) {
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1134,6 +1188,7 @@ fn hexagon_lt_rb(cell: &mut Cell, opt: &Settings) {
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1155,15 +1210,16 @@ fn hexagon_lt_rb(cell: &mut Cell, opt: &Settings) {
 }
 
 fn hexagon_bl_tr(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1176,6 +1232,7 @@ fn hexagon_bl_tr(cell: &mut Cell, opt: &Settings) {
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1197,17 +1254,18 @@ fn hexagon_bl_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn heptagon_tr(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let topright = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1220,6 +1278,7 @@ fn heptagon_tr(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topright, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1232,6 +1291,7 @@ fn heptagon_tr(cell: &mut Cell, opt: &Settings) {
         EnterType::RT,
         Edge {
             path: [Pt(1., righttop), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1242,17 +1302,18 @@ fn heptagon_tr(cell: &mut Cell, opt: &Settings) {
 }
 
 fn heptagon_bl(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_ab(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::LB,
         Edge {
             path: [Pt(0., leftbottom), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1265,6 +1326,7 @@ fn heptagon_bl(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1277,6 +1339,7 @@ fn heptagon_bl(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomleft, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1299,17 +1362,18 @@ fn heptagon_bl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn heptagon_tl(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_ab(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_ab(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let lefttop = interpolate_ab(cell.x0, cell.x3, opt);
+    let topleft = interpolate_ab(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., lefttop)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1322,6 +1386,7 @@ fn heptagon_tl(cell: &mut Cell, opt: &Settings) {
         EnterType::TL,
         Edge {
             path: [Pt(topleft, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1334,6 +1399,7 @@ fn heptagon_tl(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1356,17 +1422,18 @@ fn heptagon_tl(cell: &mut Cell, opt: &Settings) {
 }
 
 fn heptagon_br(cell: &mut Cell, opt: &Settings) {
-    let bottomright = interpolate_linear_ab(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_ab(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomright = interpolate_ab(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let rightbottom = interpolate_ab(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BR,
         Edge {
             path: [Pt(bottomright, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1379,6 +1446,7 @@ fn heptagon_br(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1391,6 +1459,7 @@ fn heptagon_br(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., rightbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1413,19 +1482,20 @@ fn heptagon_br(cell: &mut Cell, opt: &Settings) {
 }
 
 fn octagon(cell: &mut Cell, opt: &Settings) {
-    let bottomleft = interpolate_linear_a(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let bottomright = interpolate_linear_b(cell.x0, cell.x1, opt.min_v, opt.max_v);
-    let leftbottom = interpolate_linear_a(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let lefttop = interpolate_linear_b(cell.x0, cell.x3, opt.min_v, opt.max_v);
-    let topleft = interpolate_linear_a(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let topright = interpolate_linear_b(cell.x3, cell.x2, opt.min_v, opt.max_v);
-    let righttop = interpolate_linear_b(cell.x1, cell.x2, opt.min_v, opt.max_v);
-    let rightbottom = interpolate_linear_a(cell.x1, cell.x2, opt.min_v, opt.max_v);
+    let bottomleft = interpolate_a(cell.x0, cell.x1, opt);
+    let bottomright = interpolate_b(cell.x0, cell.x1, opt);
+    let leftbottom = interpolate_a(cell.x0, cell.x3, opt);
+    let lefttop = interpolate_b(cell.x0, cell.x3, opt);
+    let topleft = interpolate_a(cell.x3, cell.x2, opt);
+    let topright = interpolate_b(cell.x3, cell.x2, opt);
+    let righttop = interpolate_b(cell.x1, cell.x2, opt);
+    let rightbottom = interpolate_a(cell.x1, cell.x2, opt);
 
     cell.edges.insert(
         EnterType::BL,
         Edge {
             path: [Pt(bottomleft, 0.), Pt(0., leftbottom)],
+            via: None,
             move_info: MoveInfo {
                 x: -1,
                 y: 0,
@@ -1438,6 +1508,7 @@ fn octagon(cell: &mut Cell, opt: &Settings) {
         EnterType::LT,
         Edge {
             path: [Pt(0., lefttop), Pt(topleft, 1.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: 1,
@@ -1450,6 +1521,7 @@ fn octagon(cell: &mut Cell, opt: &Settings) {
         EnterType::TR,
         Edge {
             path: [Pt(topright, 1.), Pt(1., righttop)],
+            via: None,
             move_info: MoveInfo {
                 x: 1,
                 y: 0,
@@ -1462,6 +1534,7 @@ fn octagon(cell: &mut Cell, opt: &Settings) {
         EnterType::RB,
         Edge {
             path: [Pt(1., rightbottom), Pt(bottomright, 0.)],
+            via: None,
             move_info: MoveInfo {
                 x: 0,
                 y: -1,
@@ -1484,20 +1557,130 @@ fn octagon(cell: &mut Cell, opt: &Settings) {
     // );
 }
 
-pub(crate) fn prepare_cell(
+/// The side of a cell between its quarters `side` and `side + 1` (the quarters
+/// being numbered as the corners of the cell): the name of its middle point,
+/// its middle point and the move to the next cell when leaving the cell through it.
+fn pixel_side(side: usize) -> (EnterType, Pt, MoveInfo) {
+    match side {
+        0 => (
+            EnterType::BL,
+            Pt(0.5, 0.),
+            MoveInfo {
+                x: 0,
+                y: -1,
+                enter: EnterType::TL,
+            },
+        ),
+        1 => (
+            EnterType::RB,
+            Pt(1., 0.5),
+            MoveInfo {
+                x: 1,
+                y: 0,
+                enter: EnterType::LB,
+            },
+        ),
+        2 => (
+            EnterType::TL,
+            Pt(0.5, 1.),
+            MoveInfo {
+                x: 0,
+                y: 1,
+                enter: EnterType::BL,
+            },
+        ),
+        _ => (
+            EnterType::LB,
+            Pt(0., 0.5),
+            MoveInfo {
+                x: -1,
+                y: 0,
+                enter: EnterType::RB,
+            },
+        ),
+    }
+}
+
+/// Compute the edges of a cell read as pixels (see [`Interpolation::Pixelated`]):
+/// each quarter of the cell belongs to the pixel of its corner, so the boundary
+/// of the band goes from the middle of a side to the center of the cell,
+/// then to the middle of another side, around each run of quarters inside the band.
+fn pixel_edges(cell: &mut Cell, cval: u8) {
+    let inside = |quarter: usize| (cval >> (2 * (quarter % 4))) & 3 == 1;
+    for start in (0..4).filter(|&q| inside(q) && !inside(q + 3)) {
+        let mut end = start;
+        while inside(end + 1) {
+            end += 1;
+        }
+        let (enter, from, _) = pixel_side((start + 3) % 4);
+        let (_, to, move_info) = pixel_side(end % 4);
+        // The path is a straight line when it crosses two quarters
+        let via = (end - start != 1).then_some(Pt(0.5, 0.5));
+        cell.edges.insert(
+            enter,
+            Edge {
+                path: [from, to],
+                via,
+                move_info,
+            },
+        );
+    }
+}
+
+/// Classify a value of the grid (as 0 for below, 1 for within and 2 for above the band).
+///
+/// A missing value is classified using its valid neighbours: it is considered
+/// to be above the band if it is next to values above the band and to none below it,
+/// and below the band otherwise. This way, no band is created between a missing value
+/// and the valid values that are outside of the current band.
+fn trit<T: GridValue>(v: f64, p: GridCoord, data: &BorrowedGrid<T>, opt: &Settings) -> u8 {
+    if v.is_nan() {
+        let (x, y) = p;
+        let neighbours = [
+            x.checked_sub(1).map(|x| (x, y)),
+            Some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            Some((x, y + 1)),
+        ];
+        let (mut above, mut below) = (false, false);
+        for v in neighbours
+            .iter()
+            .flatten()
+            .filter_map(|p| data.get_value(p))
+        {
+            above |= v > opt.max_v;
+            below |= v < opt.min_v;
+        }
+        if above && !below {
+            2
+        } else {
+            0
+        }
+    } else if v < opt.min_v {
+        0
+    } else if v > opt.max_v {
+        2
+    } else {
+        1
+    }
+}
+
+pub(crate) fn prepare_cell<T: GridValue>(
     x: usize,
     y: usize,
-    data: &BorrowedGrid<f64>,
+    data: &BorrowedGrid<T>,
     opt: &Settings,
 ) -> Result<Option<Cell>> {
     /*  compose the 4-trit corner representation */
     let mut cval: u8 = 0;
-    let x3 = *data.get(&(x, y + 1)).unwrap_or(&f64::NAN);
-    let x2 = *data.get(&(x + 1, y + 1)).unwrap_or(&f64::NAN);
-    let x1 = *data.get(&(x + 1, y)).unwrap_or(&f64::NAN);
-    let x0 = *data.get(&(x, y)).unwrap_or(&f64::NAN);
-
-    if x0.is_nan() || x1.is_nan() || x2.is_nan() || x3.is_nan() {
+    let x3 = data.get_value(&(x, y + 1)).unwrap_or(f64::NAN);
+    let x2 = data.get_value(&(x + 1, y + 1)).unwrap_or(f64::NAN);
+    let x1 = data.get_value(&(x + 1, y)).unwrap_or(f64::NAN);
+    let x0 = data.get_value(&(x, y)).unwrap_or(f64::NAN);
+
+    if opt.nodata_boundary == NoDataBoundary::DropCell
+        && (x0.is_nan() || x1.is_nan() || x2.is_nan() || x3.is_nan())
+    {
         return Ok(None);
     }
 
@@ -1533,34 +1716,10 @@ pub(crate) fn prepare_cell(
      * x1 the 2nd and 3rd bit, and so on. This essentially
      * enables us to work with a single integer number
      */
-    cval |= if x3 < opt.min_v {
-        0
-    } else if x3 > opt.max_v {
-        128
-    } else {
-        64
-    };
-    cval |= if x2 < opt.min_v {
-        0
-    } else if x2 > opt.max_v {
-        32
-    } else {
-        16
-    };
-    cval |= if x1 < opt.min_v {
-        0
-    } else if x1 > opt.max_v {
-        8
-    } else {
-        4
-    };
-    cval |= if x0 < opt.min_v {
-        0
-    } else if x0 > opt.max_v {
-        2
-    } else {
-        1
-    };
+    cval |= trit(x3, (x, y + 1), data, opt) << 6;
+    cval |= trit(x2, (x + 1, y + 1), data, opt) << 4;
+    cval |= trit(x1, (x + 1, y), data, opt) << 2;
+    cval |= trit(x0, (x, y), data, opt);
 
     /*
      * cell center average trit for ambiguous cases, where
@@ -1568,7 +1727,7 @@ pub(crate) fn prepare_cell(
      * 1 ... within iso band
      * 2 ... above isoband
      */
-    let mut center_avg: u8 = 0;
+    let center_avg: u8;
 
     let mut cell = Cell {
         // x,
@@ -1581,6 +1740,11 @@ pub(crate) fn prepare_cell(
         edges: FxHashMap::default(),
     };
 
+    if opt.interpolation == Interpolation::Pixelated {
+        pixel_edges(&mut cell, cval);
+        return Ok(Some(cell));
+    }
+
     // I tried storing the functions in a hashmap (FxHashMap) and in a Vec
     // but none of them were faster than the match statement
     // (which I decided to keep for now for readability).
@@ -1855,7 +2019,9 @@ pub(crate) fn prepare_cell(
             hexagon_bl_tr(&mut cell, opt);
         }
         17 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_bl(&mut cell, opt);
@@ -1865,7 +2031,9 @@ pub(crate) fn prepare_cell(
             }
         }
         68 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_tl(&mut cell, opt);
@@ -1875,7 +2043,9 @@ pub(crate) fn prepare_cell(
             }
         }
         153 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_bl(&mut cell, opt);
@@ -1885,7 +2055,9 @@ pub(crate) fn prepare_cell(
             }
         }
         102 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_tl(&mut cell, opt);
@@ -1895,7 +2067,9 @@ pub(crate) fn prepare_cell(
             }
         }
         152 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_tr(&mut cell, opt);
@@ -1905,7 +2079,9 @@ pub(crate) fn prepare_cell(
             }
         }
         137 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_bl(&mut cell, opt);
@@ -1915,7 +2091,9 @@ pub(crate) fn prepare_cell(
             }
         }
         98 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_tl(&mut cell, opt);
@@ -1925,7 +2103,9 @@ pub(crate) fn prepare_cell(
             }
         }
         38 => {
-            compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 0 */
             if center_avg == 2 {
                 triangle_br(&mut cell, opt);
@@ -1935,7 +2115,9 @@ pub(crate) fn prepare_cell(
             }
         }
         18 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_tr(&mut cell, opt);
@@ -1945,7 +2127,9 @@ pub(crate) fn prepare_cell(
             }
         }
         33 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_bl(&mut cell, opt);
@@ -1955,7 +2139,9 @@ pub(crate) fn prepare_cell(
             }
         }
         72 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_tl(&mut cell, opt);
@@ -1965,7 +2151,9 @@ pub(crate) fn prepare_cell(
             }
         }
         132 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             /* should never be center_avg === 2 */
             if center_avg == 0 {
                 triangle_br(&mut cell, opt);
@@ -1975,7 +2163,9 @@ pub(crate) fn prepare_cell(
             }
         }
         136 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
             if center_avg == 0 {
                 tetragon_tl(&mut cell, opt);
                 tetragon_br(&mut cell, opt);
@@ -1987,7 +2177,9 @@ pub(crate) fn prepare_cell(
             }
         }
         34 => {
-            center_avg = compute_center_average(x0, x1, x2, x3, opt.min_v, opt.max_v);
+            center_avg = opt
+                .saddle_resolution
+                .center([x0, x1, x2, x3], opt.min_v, opt.max_v);
 
             if center_avg == 0 {
                 tetragon_bl(&mut cell, opt);
@@ -2023,13 +2215,26 @@ pub(crate) fn prepare_cell(
 
 #[cfg(test)]
 mod test {
-    use crate::shape_coordinates::compute_center_average;
+    use crate::isobands::SaddleResolution;
 
     #[test]
     fn test_compute_center_average() {
-        assert_eq!(compute_center_average(0., 0., 0., 0., 0., 1.), 1);
-        assert_eq!(compute_center_average(1., 1., 0., 0., 0., 0.), 2);
-        assert_eq!(compute_center_average(1., 1., 0., 0., 0., 1.), 1);
+        let center =
+            |corners, min_v, max_v| SaddleResolution::CenterAverage.center(corners, min_v, max_v);
+        assert_eq!(center([0., 0., 0., 0.], 0., 1.), 1);
+        assert_eq!(center([1., 1., 0., 0.], 0., 0.), 2);
+        assert_eq!(center([1., 1., 0., 0.], 0., 1.), 1);
+    }
+
+    #[test]
+    fn test_asymptotic_decider() {
+        // The saddle point of the bilinear interpolation is at 1.83 / 3.61 ~= 0.507
+        let center = |min_v, max_v| {
+            SaddleResolution::AsymptoticDecider.center([3., 0., 0.61, 0.], min_v, max_v)
+        };
+        assert_eq!(center(0.6, 3.), 0);
+        assert_eq!(center(0.5, 3.), 1);
+        assert_eq!(center(0., 0.5), 2);
     }
 }
 
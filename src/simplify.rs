@@ -0,0 +1,349 @@
+use crate::isobands::BandRaw;
+use crate::topology::Topology;
+use geo_types::Point;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// The number of times the tolerance is halved for an arc whose simplification
+/// changes the topology, before keeping all the points of the arc.
+const MAX_REFINEMENTS: u32 = 8;
+
+/// Simplifies the rings of the bands (in grid coordinates) using the Douglas-Peucker
+/// algorithm, while preserving the topology of the bands.
+///
+/// The rings are cut into arcs at the points where they meet other rings, so that
+/// each boundary shared by two adjacent bands is simplified only once. The arcs whose
+/// simplification makes rings cross each other, moves a ring over a point of another ring
+/// or collapses a ring, are simplified again with a smaller tolerance.
+pub(crate) fn simplify_bands(bands: &mut [BandRaw], tolerance: f64) {
+    if tolerance.is_nan() || tolerance <= 0. {
+        return;
+    }
+    let topology = Topology::new(bands);
+    let mut simplifier = Simplifier {
+        arcs: topology
+            .arcs
+            .iter()
+            .map(|points| Arc {
+                kept: (0..points.len()).collect(),
+                tolerance,
+                refinements: 0,
+            })
+            .collect(),
+        topology: &topology,
+    };
+    simplifier.simplify();
+    let arcs = simplifier
+        .arcs
+        .iter()
+        .zip(topology.arcs.iter())
+        .map(|(arc, points)| {
+            arc.kept
+                .iter()
+                .map(|&k| topology.points[points[k]])
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    topology.write(bands, &arcs);
+}
+
+/// The simplification of an arc of the topology.
+struct Arc {
+    /// The indices of the points (of the arc) kept by the simplification
+    kept: Vec<usize>,
+    /// The tolerance used to simplify the arc
+    tolerance: f64,
+    /// The number of times the tolerance was reduced
+    refinements: u32,
+}
+
+struct Simplifier<'a> {
+    topology: &'a Topology,
+    /// The simplification of each arc of the topology
+    arcs: Vec<Arc>,
+}
+
+impl Simplifier<'_> {
+    fn is_simplified(&self, i: usize) -> bool {
+        self.arcs[i].kept.len() < self.topology.arcs[i].len()
+    }
+
+    fn simplify(&mut self) {
+        for i in 0..self.arcs.len() {
+            self.simplify_arc(i);
+        }
+        loop {
+            let invalid_arcs = self.invalid_arcs();
+            if invalid_arcs.is_empty() {
+                break;
+            }
+            for i in invalid_arcs {
+                let arc = &mut self.arcs[i];
+                arc.refinements += 1;
+                if arc.refinements > MAX_REFINEMENTS {
+                    arc.kept = (0..self.topology.arcs[i].len()).collect();
+                } else {
+                    arc.tolerance /= 2.;
+                    self.simplify_arc(i);
+                }
+            }
+        }
+    }
+
+    fn simplify_arc(&mut self, i: usize) {
+        let (arc, points) = (&self.arcs[i], &self.topology.arcs[i]);
+        let coords = points
+            .iter()
+            .map(|&id| self.topology.points[id])
+            .collect::<Vec<_>>();
+        let last = coords.len() - 1;
+        let kept = if points[0] == points[last] {
+            // A closed arc is split at its farthest point from its start
+            let far = (1..last)
+                .max_by(|&a, &b| {
+                    squared_distance(&coords[0], &coords[a])
+                        .total_cmp(&squared_distance(&coords[0], &coords[b]))
+                })
+                .unwrap_or(last);
+            let mut kept = douglas_peucker(&coords, 0, far, arc.tolerance);
+            kept.pop();
+            kept.extend(douglas_peucker(&coords, far, last, arc.tolerance));
+            kept
+        } else {
+            douglas_peucker(&coords, 0, last, arc.tolerance)
+        };
+        self.arcs[i].kept = kept;
+    }
+
+    /// Find the simplified arcs that change the topology of the rings.
+    fn invalid_arcs(&self) -> FxHashSet<usize> {
+        let mut invalid_arcs = FxHashSet::default();
+
+        // The rings must keep at least 3 points
+        for ring in self.topology.rings.iter().flatten() {
+            let n_points = ring
+                .iter()
+                .map(|&(i, _)| self.arcs[i].kept.len() - 1)
+                .sum::<usize>();
+            if n_points < 3 {
+                invalid_arcs.extend(
+                    ring.iter()
+                        .map(|&(i, _)| i)
+                        .filter(|&i| self.is_simplified(i)),
+                );
+            }
+        }
+
+        // The (simplified) segments, as (arc, index of the first point in `kept`),
+        // and the kept points, indexed by the buckets of a grid
+        let bucket_size = self.arcs.iter().map(|arc| arc.tolerance).fold(1., f64::max);
+        let bucket = |p: &Point<f64>| {
+            (
+                (p.x() / bucket_size).floor() as i64,
+                (p.y() / bucket_size).floor() as i64,
+            )
+        };
+        let mut segments: FxHashMap<(i64, i64), Vec<(usize, usize)>> = FxHashMap::default();
+        let mut kept_points: FxHashMap<(i64, i64), Vec<usize>> = FxHashMap::default();
+        let mut seen = FxHashSet::default();
+        for (i, arc) in self.arcs.iter().enumerate() {
+            for k in 0..arc.kept.len() - 1 {
+                let (a, b) = self.segment(i, k);
+                let (min, max) = (bucket(a), bucket(b));
+                for x in min.0.min(max.0)..=min.0.max(max.0) {
+                    for y in min.1.min(max.1)..=min.1.max(max.1) {
+                        segments.entry((x, y)).or_default().push((i, k));
+                    }
+                }
+            }
+            for &k in arc.kept.iter() {
+                let id = self.topology.arcs[i][k];
+                if seen.insert(id) {
+                    kept_points
+                        .entry(bucket(&self.topology.points[id]))
+                        .or_default()
+                        .push(id);
+                }
+            }
+        }
+
+        // The simplified segments must not cross other segments
+        for bucket_segments in segments.values() {
+            for (n, &(i, k)) in bucket_segments.iter().enumerate() {
+                for &(j, l) in bucket_segments[n + 1..].iter() {
+                    let (simplified_a, simplified_b) = (
+                        self.is_simplified_segment(i, k),
+                        self.is_simplified_segment(j, l),
+                    );
+                    if !simplified_a && !simplified_b {
+                        continue;
+                    }
+                    let ids_a = self.segment_ids(i, k);
+                    let ids_b = self.segment_ids(j, l);
+                    if ids_a.0 == ids_b.0
+                        || ids_a.0 == ids_b.1
+                        || ids_a.1 == ids_b.0
+                        || ids_a.1 == ids_b.1
+                    {
+                        continue;
+                    }
+                    let (a, b) = self.segment(i, k);
+                    let (c, d) = self.segment(j, l);
+                    if segments_intersect(a, b, c, d) {
+                        if simplified_a {
+                            invalid_arcs.insert(i);
+                        }
+                        if simplified_b {
+                            invalid_arcs.insert(j);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The area between a simplified segment and the points it replaces
+        // must not contain other points
+        for (i, arc) in self.arcs.iter().enumerate() {
+            if invalid_arcs.contains(&i) {
+                continue;
+            }
+            for k in 0..arc.kept.len() - 1 {
+                if !self.is_simplified_segment(i, k) {
+                    continue;
+                }
+                let ids = &self.topology.arcs[i][arc.kept[k]..=arc.kept[k + 1]];
+                let coords = ids
+                    .iter()
+                    .map(|&id| self.topology.points[id])
+                    .collect::<Vec<_>>();
+                let (min, max) = coords.iter().fold(
+                    ((i64::MAX, i64::MAX), (i64::MIN, i64::MIN)),
+                    |(min, max), p| {
+                        let b = bucket(p);
+                        (
+                            (min.0.min(b.0), min.1.min(b.1)),
+                            (max.0.max(b.0), max.1.max(b.1)),
+                        )
+                    },
+                );
+                let replaces_point = (min.0..=max.0)
+                    .flat_map(|x| (min.1..=max.1).map(move |y| (x, y)))
+                    .filter_map(|key| kept_points.get(&key))
+                    .flatten()
+                    .any(|id| {
+                        !ids.contains(id) && ring_contains(&coords, &self.topology.points[*id])
+                    });
+                if replaces_point {
+                    invalid_arcs.insert(i);
+                    break;
+                }
+            }
+        }
+
+        invalid_arcs
+    }
+
+    fn is_simplified_segment(&self, i: usize, k: usize) -> bool {
+        self.arcs[i].kept[k + 1] - self.arcs[i].kept[k] > 1
+    }
+
+    fn segment_ids(&self, i: usize, k: usize) -> (usize, usize) {
+        let (arc, points) = (&self.arcs[i], &self.topology.arcs[i]);
+        (points[arc.kept[k]], points[arc.kept[k + 1]])
+    }
+
+    fn segment(&self, i: usize, k: usize) -> (&Point<f64>, &Point<f64>) {
+        let (a, b) = self.segment_ids(i, k);
+        (&self.topology.points[a], &self.topology.points[b])
+    }
+}
+
+/// Simplify the points between `start` and `end` (both kept), returning
+/// the indices of the kept points.
+fn douglas_peucker(coords: &[Point<f64>], start: usize, end: usize, tolerance: f64) -> Vec<usize> {
+    let mut keep = vec![false; end - start + 1];
+    keep[0] = true;
+    keep[end - start] = true;
+    let squared_tolerance = tolerance * tolerance;
+    let mut stack = vec![(start, end)];
+    while let Some((first, last)) = stack.pop() {
+        if last - first < 2 {
+            continue;
+        }
+        let (index, distance) = (first + 1..last)
+            .map(|i| {
+                (
+                    i,
+                    squared_segment_distance(&coords[i], &coords[first], &coords[last]),
+                )
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        if distance > squared_tolerance {
+            keep[index - start] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+    keep.iter()
+        .enumerate()
+        .filter(|(_, &k)| k)
+        .map(|(i, _)| start + i)
+        .collect()
+}
+
+fn squared_distance(a: &Point<f64>, b: &Point<f64>) -> f64 {
+    (a.x() - b.x()).powi(2) + (a.y() - b.y()).powi(2)
+}
+
+/// Squared distance from `p` to the segment `a` - `b`.
+fn squared_segment_distance(p: &Point<f64>, a: &Point<f64>, b: &Point<f64>) -> f64 {
+    let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+    let length = dx * dx + dy * dy;
+    if length == 0. {
+        return squared_distance(p, a);
+    }
+    let t = (((p.x() - a.x()) * dx + (p.y() - a.y()) * dy) / length).clamp(0., 1.);
+    (p.x() - a.x() - t * dx).powi(2) + (p.y() - a.y() - t * dy).powi(2)
+}
+
+fn orientation(a: &Point<f64>, b: &Point<f64>, c: &Point<f64>) -> f64 {
+    (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+}
+
+fn on_segment(a: &Point<f64>, b: &Point<f64>, p: &Point<f64>) -> bool {
+    p.x() >= a.x().min(b.x())
+        && p.x() <= a.x().max(b.x())
+        && p.y() >= a.y().min(b.y())
+        && p.y() <= a.y().max(b.y())
+}
+
+/// Whether the segments `a` - `b` and `c` - `d` intersect (or touch).
+fn segments_intersect(a: &Point<f64>, b: &Point<f64>, c: &Point<f64>, d: &Point<f64>) -> bool {
+    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+    if ((d1 > 0. && d2 < 0.) || (d1 < 0. && d2 > 0.))
+        && ((d3 > 0. && d4 < 0.) || (d3 < 0. && d4 > 0.))
+    {
+        return true;
+    }
+    (d1 == 0. && on_segment(c, d, a))
+        || (d2 == 0. && on_segment(c, d, b))
+        || (d3 == 0. && on_segment(a, b, c))
+        || (d4 == 0. && on_segment(a, b, d))
+}
+
+/// Whether the point is inside the ring (given by its points, closed or not).
+fn ring_contains(ring: &[Point<f64>], point: &Point<f64>) -> bool {
+    let mut contains = false;
+    let mut j = ring.len() - 1;
+    for (i, pi) in ring.iter().enumerate() {
+        let pj = &ring[j];
+        if ((pi.y() > point.y()) != (pj.y() > point.y()))
+            && (point.x() < (pj.x() - pi.x()) * (point.y() - pi.y()) / (pj.y() - pi.y()) + pi.x())
+        {
+            contains = !contains;
+        }
+        j = i;
+    }
+    contains
+}
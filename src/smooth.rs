@@ -0,0 +1,207 @@
+use crate::isobands::BandRaw;
+use crate::topology::Topology;
+use geo_types::Point;
+
+/// A smoothing algorithm, applied to the boundaries of the bands
+/// (see [`ContourBuilder::smooth`](crate::ContourBuilder::smooth)).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoothing {
+    /// Chaikin's corner cutting, with the given number of iterations
+    /// (each iteration replaces each segment by two points, at a quarter
+    /// and three quarters of its length)
+    Chaikin(usize),
+    /// A centripetal Catmull-Rom spline passing through the original points, with
+    /// the given number of points inserted between two consecutive original points
+    CatmullRom(usize),
+}
+
+/// Smooths the rings of the bands (in grid coordinates), for a grid
+/// of size `width` x `height`.
+///
+/// The rings are cut into arcs at the points where they meet other rings, so that
+/// each boundary shared by two adjacent bands is smoothed only once (and stays shared
+/// by both bands). The ends of the arcs, as well as the points on the frame of the grid,
+/// are not moved.
+pub(crate) fn smooth_bands(
+    bands: &mut [BandRaw],
+    smoothing: Smoothing,
+    width: usize,
+    height: usize,
+) {
+    if let Smoothing::Chaikin(0) | Smoothing::CatmullRom(0) = smoothing {
+        return;
+    }
+    let topology = Topology::new(bands);
+    let (max_x, max_y) = (
+        width.saturating_sub(1) as f64,
+        height.saturating_sub(1) as f64,
+    );
+    let is_fixed = |id: usize| {
+        let p = topology.points[id];
+        topology.junctions.contains(&id)
+            || p.x() <= 0.
+            || p.y() <= 0.
+            || p.x() >= max_x
+            || p.y() >= max_y
+    };
+
+    let arcs = topology
+        .arcs
+        .iter()
+        .map(|ids| {
+            let last = ids.len() - 1;
+            let closed = ids[0] == ids[last];
+            let mut ids = ids.clone();
+            if closed {
+                match (0..last).find(|&k| is_fixed(ids[k])) {
+                    None => {
+                        let coords = ids[..last]
+                            .iter()
+                            .map(|&id| topology.points[id])
+                            .collect::<Vec<_>>();
+                        return smooth_closed(&coords, smoothing);
+                    }
+                    // The arc is cut at its fixed points, starting from the first one
+                    Some(start) => {
+                        ids.pop();
+                        ids.rotate_left(start);
+                        ids.push(ids[0]);
+                    }
+                }
+            }
+
+            let mut points = vec![topology.points[ids[0]]];
+            let mut run = vec![topology.points[ids[0]]];
+            for (k, &id) in ids.iter().enumerate().skip(1) {
+                run.push(topology.points[id]);
+                if k == last || is_fixed(id) {
+                    points.extend(smooth_open(&run, smoothing).into_iter().skip(1));
+                    run = vec![topology.points[id]];
+                }
+            }
+            points
+        })
+        .collect::<Vec<_>>();
+    topology.write(bands, &arcs);
+}
+
+/// Smooths a line, without moving its first and last points.
+fn smooth_open(points: &[Point<f64>], smoothing: Smoothing) -> Vec<Point<f64>> {
+    let n = points.len() - 1;
+    if n < 2 {
+        // A single segment stays straight
+        return points.to_vec();
+    }
+    match smoothing {
+        Smoothing::Chaikin(iterations) => {
+            let mut points = points.to_vec();
+            for _ in 0..iterations {
+                let n = points.len() - 1;
+                let mut smoothed = Vec::with_capacity(2 * n);
+                smoothed.push(points[0]);
+                for i in 0..n {
+                    let (a, b) = (points[i], points[i + 1]);
+                    if i > 0 {
+                        smoothed.push(lerp(a, b, 0.25));
+                    }
+                    if i + 1 < n {
+                        smoothed.push(lerp(a, b, 0.75));
+                    }
+                }
+                smoothed.push(points[n]);
+                points = smoothed;
+            }
+            points
+        }
+        Smoothing::CatmullRom(subdivisions) => {
+            // The tangents at both ends are given by mirrored points
+            let first = lerp(points[1], points[0], 2.);
+            let last = lerp(points[n - 1], points[n], 2.);
+            let mut smoothed = Vec::with_capacity(n * (subdivisions + 1) + 1);
+            for i in 0..n {
+                let p0 = if i == 0 { first } else { points[i - 1] };
+                let p3 = if i + 1 == n { last } else { points[i + 2] };
+                catmull_rom(
+                    p0,
+                    points[i],
+                    points[i + 1],
+                    p3,
+                    subdivisions,
+                    &mut smoothed,
+                );
+            }
+            smoothed.push(points[n]);
+            smoothed
+        }
+    }
+}
+
+/// Smooths a closed ring (given without its closing point), returning the closed ring.
+fn smooth_closed(points: &[Point<f64>], smoothing: Smoothing) -> Vec<Point<f64>> {
+    let mut smoothed = match smoothing {
+        Smoothing::Chaikin(iterations) => {
+            let mut points = points.to_vec();
+            for _ in 0..iterations {
+                let n = points.len();
+                points = (0..n)
+                    .flat_map(|i| {
+                        let (a, b) = (points[i], points[(i + 1) % n]);
+                        [lerp(a, b, 0.25), lerp(a, b, 0.75)]
+                    })
+                    .collect();
+            }
+            points
+        }
+        Smoothing::CatmullRom(subdivisions) => {
+            let n = points.len();
+            let mut smoothed = Vec::with_capacity(n * (subdivisions + 1) + 1);
+            for i in 0..n {
+                catmull_rom(
+                    points[(i + n - 1) % n],
+                    points[i],
+                    points[(i + 1) % n],
+                    points[(i + 2) % n],
+                    subdivisions,
+                    &mut smoothed,
+                );
+            }
+            smoothed
+        }
+    };
+    smoothed.push(smoothed[0]);
+    smoothed
+}
+
+fn lerp(a: Point<f64>, b: Point<f64>, t: f64) -> Point<f64> {
+    Point::new(a.x() + t * (b.x() - a.x()), a.y() + t * (b.y() - a.y()))
+}
+
+/// Push `p1` and the points of the centripetal Catmull-Rom spline between `p1` and `p2`
+/// (using the Barry and Goldman's pyramidal formulation).
+fn catmull_rom(
+    p0: Point<f64>,
+    p1: Point<f64>,
+    p2: Point<f64>,
+    p3: Point<f64>,
+    subdivisions: usize,
+    points: &mut Vec<Point<f64>>,
+) {
+    let knot = |a: Point<f64>, b: Point<f64>| ((b.x() - a.x()).hypot(b.y() - a.y())).sqrt();
+    let t0 = 0.;
+    let t1 = t0 + knot(p0, p1);
+    let t2 = t1 + knot(p1, p2);
+    let t3 = t2 + knot(p2, p3);
+    points.push(p1);
+    if t1 == t0 || t2 == t1 || t3 == t2 {
+        return;
+    }
+    for k in 1..=subdivisions {
+        let t = t1 + (t2 - t1) * k as f64 / (subdivisions + 1) as f64;
+        let a1 = lerp(p0, p1, (t - t0) / (t1 - t0));
+        let a2 = lerp(p1, p2, (t - t1) / (t2 - t1));
+        let a3 = lerp(p2, p3, (t - t2) / (t3 - t2));
+        let b1 = lerp(a1, a2, (t - t0) / (t2 - t0));
+        let b2 = lerp(a2, a3, (t - t1) / (t3 - t1));
+        points.push(lerp(b1, b2, (t - t1) / (t2 - t1)));
+    }
+}
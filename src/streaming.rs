@@ -0,0 +1,667 @@
+use crate::area::area;
+use crate::errors::{new_error, ErrorKind, Result};
+use crate::grid::{BorrowedGrid, GridValue};
+use crate::isobands::{Cell, ContourBuilder, EnterType, NoDataBoundary, Pt, Settings};
+use crate::polygons::frame_ring;
+use crate::quadtree::QuadTree;
+use crate::shape_coordinates::prepare_cell;
+use crate::utils::is_winding_correct;
+use geo_types::{LineString, Point};
+use rustc_hash::FxHashMap;
+use std::collections::VecDeque;
+
+/// A closed ring of a band, emitted by the [`StripContourer`]
+/// as soon as the rows containing all its points have been read.
+#[derive(Debug)]
+pub struct BandRing {
+    /// The index of the band (i.e. the index of its minimum value in the thresholds)
+    pub band: usize,
+    /// The minimum value of the band
+    pub min_v: f64,
+    /// The maximum value of the band
+    pub max_v: f64,
+    /// Whether the ring is the exterior ring of a polygon (or one of its holes)
+    pub is_exterior: bool,
+    /// The ring itself
+    pub ring: LineString<f64>,
+}
+
+/// The identifier of a point of the boundary of a band, expressed on a grid of doubled
+/// resolution (like the edges of the isolines): the first ([`FIRST`]) or the second
+/// ([`SECOND`]) crossing point of an edge, or a node of the grid ([`NODE`]).
+type PointKey = (usize, usize, u8);
+
+const FIRST: u8 = 0;
+const SECOND: u8 = 1;
+const NODE: u8 = 2;
+
+/// The key of the crossing point of the cell `(x, y)` given by its entry type,
+/// so that a crossing point shared by two cells has the same key in both cells.
+fn crossing_key(x: usize, y: usize, enter: &EnterType) -> PointKey {
+    let (x, y) = (2 * x, 2 * y);
+    match enter {
+        EnterType::BL => (x + 1, y, FIRST),
+        EnterType::BR => (x + 1, y, SECOND),
+        EnterType::TL => (x + 1, y + 2, FIRST),
+        EnterType::TR => (x + 1, y + 2, SECOND),
+        EnterType::LB => (x, y + 1, FIRST),
+        EnterType::LT => (x, y + 1, SECOND),
+        EnterType::RB => (x + 2, y + 1, FIRST),
+        EnterType::RT => (x + 2, y + 1, SECOND),
+    }
+}
+
+/// The entry type, in the cell that a path leaves, of the crossing point
+/// through which the path enters the next cell.
+fn exit_type(enter: &EnterType) -> EnterType {
+    match enter {
+        EnterType::TL => EnterType::BL,
+        EnterType::TR => EnterType::BR,
+        EnterType::BL => EnterType::TL,
+        EnterType::BR => EnterType::TR,
+        EnterType::LB => EnterType::RB,
+        EnterType::LT => EnterType::RT,
+        EnterType::RB => EnterType::LB,
+        EnterType::RT => EnterType::LT,
+    }
+}
+
+/// The order in which the edges of the cells are used to start the rings
+/// in [`ContourBuilder::contours`]: the rings start at the first point of their first edge,
+/// which tells which ring is a hole when the rings touch each other.
+type Rank = (usize, usize, usize);
+
+/// The order of the edges of a cell in a [`Rank`].
+const START_ORDER: [EnterType; 8] = [
+    EnterType::BL,
+    EnterType::LB,
+    EnterType::LT,
+    EnterType::TL,
+    EnterType::TR,
+    EnterType::RT,
+    EnterType::RB,
+    EnterType::BR,
+];
+
+/// What a segment of the boundary of a band follows.
+#[derive(Clone, Copy, PartialEq)]
+enum Segment {
+    /// An edge of a cell, as computed by [`prepare_cell`]
+    Cell(Rank),
+    /// A side of a cell next to a dropped cell
+    Dropped,
+    /// A side of a cell on the frame of the grid
+    Frame,
+}
+
+/// A path that is not closed yet.
+struct Fragment {
+    points: VecDeque<Point<f64>>,
+    start: PointKey,
+    end: PointKey,
+    /// Whether the path follows the edges of some cells
+    crosses: bool,
+    /// Whether the path follows the frame of the grid
+    frame: bool,
+    /// The rank of the first edge of the path and the position of its first point
+    /// (the position of the first point of the path being `-front`)
+    origin: Option<(Rank, isize)>,
+    /// The number of points added at the front of the path
+    front: isize,
+}
+
+impl Fragment {
+    /// Keeps the given origin (with a physical position) if its edge comes first.
+    fn keep_origin(&mut self, origin: Option<(Rank, isize)>) {
+        if let Some((rank, position)) = origin {
+            if !matches!(self.origin, Some((first, _)) if first <= rank) {
+                self.origin = Some((rank, position - self.front));
+            }
+        }
+    }
+
+    /// The points of the closed ring, starting at its origin.
+    fn into_ring(self) -> Vec<Point<f64>> {
+        let mut points: Vec<Point<f64>> = self.points.into();
+        if let Some((_, position)) = self.origin {
+            points.pop();
+            let start = (position + self.front) as usize % points.len();
+            points.rotate_left(start);
+            points.push(points[0]);
+        }
+        points
+    }
+}
+
+/// The open paths of a band, indexed by their start and end points.
+/// Several paths can start (or end) at the same grid node,
+/// where two valid cells only touch by a corner.
+#[derive(Default)]
+struct OpenFragments {
+    fragments: Vec<Option<Fragment>>,
+    free: Vec<usize>,
+    by_start: FxHashMap<PointKey, Vec<usize>>,
+    by_end: FxHashMap<PointKey, Vec<usize>>,
+}
+
+fn take_index(map: &mut FxHashMap<PointKey, Vec<usize>>, key: &PointKey) -> Option<usize> {
+    let indexes = map.get_mut(key)?;
+    let index = indexes.pop();
+    if indexes.is_empty() {
+        map.remove(key);
+    }
+    index
+}
+
+impl OpenFragments {
+    fn len(&self) -> usize {
+        self.fragments.len() - self.free.len()
+    }
+
+    /// Add an oriented path (going through the given points, the first and the last one
+    /// being at `start` and `end`) to the open paths, returning the ring it closes (if any).
+    fn add_path(
+        &mut self,
+        start: PointKey,
+        end: PointKey,
+        points: &[Point<f64>],
+        segment: Segment,
+    ) -> Option<Fragment> {
+        let rank = match segment {
+            Segment::Cell(rank) => Some(rank),
+            _ => None,
+        };
+        let crosses = rank.is_some();
+        let frame = segment == Segment::Frame;
+        match (
+            take_index(&mut self.by_end, &start),
+            take_index(&mut self.by_start, &end),
+        ) {
+            (Some(before), Some(after)) if before == after => {
+                // The path closes a ring
+                let mut fragment = self.fragments[before].take().unwrap();
+                self.free.push(before);
+                fragment.keep_origin(rank.map(|rank| (rank, fragment.points.len() as isize - 1)));
+                fragment.points.extend(&points[1..]);
+                fragment.crosses |= crosses;
+                fragment.frame |= frame;
+                Some(fragment)
+            }
+            (Some(before), Some(after)) => {
+                // The path joins two paths
+                let fragment = self.fragments[after].take().unwrap();
+                self.free.push(after);
+                let indexes = self.by_end.get_mut(&fragment.end).unwrap();
+                let i = indexes.iter().position(|&i| i == after).unwrap();
+                indexes[i] = before;
+                let joined = self.fragments[before].as_mut().unwrap();
+                joined.keep_origin(rank.map(|rank| (rank, joined.points.len() as isize - 1)));
+                joined.points.extend(&points[1..points.len() - 1]);
+                let offset = joined.points.len() as isize + fragment.front;
+                joined.keep_origin(fragment.origin.map(|(rank, i)| (rank, i + offset)));
+                joined.points.extend(fragment.points);
+                joined.end = fragment.end;
+                joined.crosses |= crosses || fragment.crosses;
+                joined.frame |= frame || fragment.frame;
+                None
+            }
+            (Some(before), None) => {
+                let fragment = self.fragments[before].as_mut().unwrap();
+                fragment.keep_origin(rank.map(|rank| (rank, fragment.points.len() as isize - 1)));
+                fragment.points.extend(&points[1..]);
+                fragment.end = end;
+                fragment.crosses |= crosses;
+                fragment.frame |= frame;
+                self.by_end.entry(end).or_default().push(before);
+                None
+            }
+            (None, Some(after)) => {
+                let fragment = self.fragments[after].as_mut().unwrap();
+                for &point in points[..points.len() - 1].iter().rev() {
+                    fragment.points.push_front(point);
+                }
+                fragment.front += points.len() as isize - 1;
+                fragment.keep_origin(rank.map(|rank| (rank, 0)));
+                fragment.start = start;
+                fragment.crosses |= crosses;
+                fragment.frame |= frame;
+                self.by_start.entry(start).or_default().push(after);
+                None
+            }
+            (None, None) => {
+                let fragment = Fragment {
+                    points: points.iter().copied().collect(),
+                    start,
+                    end,
+                    crosses,
+                    frame,
+                    origin: rank.map(|rank| (rank, 0)),
+                    front: 0,
+                };
+                let index = match self.free.pop() {
+                    Some(index) => {
+                        self.fragments[index] = Some(fragment);
+                        index
+                    }
+                    None => {
+                        self.fragments.push(Some(fragment));
+                        self.fragments.len() - 1
+                    }
+                };
+                self.by_start.entry(start).or_default().push(index);
+                self.by_end.entry(end).or_default().push(index);
+                None
+            }
+        }
+    }
+}
+
+/// The paths of a band read so far.
+struct BandPaths {
+    open: OpenFragments,
+    /// Whether all the values read on the frame of the grid are inside the band
+    /// (missing values being inside when the cells are dropped), in which case
+    /// the frame ring is added as in [`ContourBuilder::contours`]
+    frame_in_band: bool,
+    /// Whether a ring following both the edges of some cells and the frame of the grid
+    /// was closed, in which case the frame ring is not added
+    walked_frame: bool,
+}
+
+/// A side of a cell: the position of the neighbour cell (which is also the move
+/// of the paths leaving the cell through the side), the start and end corners
+/// and the entry types of the crossing points on the side.
+type CellSide = ((i32, i32), (usize, usize), (usize, usize), [EnterType; 2]);
+
+/// The sides of a cell, in the order they are walked around the cell
+/// (keeping the cell on the right, like the band).
+const SIDES: [CellSide; 4] = [
+    ((1, 0), (1, 1), (1, 0), [EnterType::RB, EnterType::RT]),
+    ((0, -1), (1, 0), (0, 0), [EnterType::BL, EnterType::BR]),
+    ((-1, 0), (0, 0), (0, 1), [EnterType::LB, EnterType::LT]),
+    ((0, 1), (0, 1), (1, 1), [EnterType::TL, EnterType::TR]),
+];
+
+/// Streaming contours generator, created by [`ContourBuilder::strip_contourer`],
+/// that reads the grid by strips of rows (from top to bottom) instead of
+/// requiring the whole grid to be in memory.
+///
+/// The cells are computed as in [`ContourBuilder::contours`] (whatever the options),
+/// and only the last three rows of values and the paths that are not closed yet
+/// (i.e. that cross the last rows read) are kept in memory:
+/// rings are returned by [`StripContourer::push_strip`] as soon as they are closed.
+pub struct StripContourer<'a> {
+    builder: &'a ContourBuilder,
+    /// The settings, minimum and maximum values of each band
+    bands: Vec<(Settings, f64, f64)>,
+    /// The values of the last rows read (NaN for the missing values),
+    /// starting from the row `first_row`
+    buffer: Vec<f64>,
+    first_row: usize,
+    /// The row of the next cells to process (the cells of a row are processed
+    /// once the row after their corners is read, as the missing values depend on their neighbours)
+    next_cell_row: usize,
+    /// The number of rows read so far
+    rows: usize,
+    /// The paths of each band
+    paths: Vec<BandPaths>,
+}
+
+impl<'a> StripContourer<'a> {
+    pub(crate) fn new(builder: &'a ContourBuilder, thresholds: &[f64]) -> Result<Self> {
+        let bands = builder.band_settings(thresholds)?;
+        if builder.width * builder.height == 0 {
+            return Err(new_error(ErrorKind::BadData));
+        }
+        builder.check_options()?;
+
+        Ok(StripContourer {
+            builder,
+            paths: bands
+                .iter()
+                .map(|_| BandPaths {
+                    open: OpenFragments::default(),
+                    frame_in_band: true,
+                    walked_frame: false,
+                })
+                .collect(),
+            bands,
+            buffer: Vec::with_capacity(3 * builder.width),
+            first_row: 0,
+            next_cell_row: 0,
+            rows: 0,
+        })
+    }
+
+    /// Reads the next rows of the grid (the length of the strip must be a multiple
+    /// of the width of the grid) and returns the rings that were closed by these rows.
+    pub fn push_strip<T: GridValue>(&mut self, strip: &[T]) -> Result<Vec<BandRing>> {
+        let rings = self.push_strip_raw(strip)?;
+        Ok(self.to_band_rings(rings))
+    }
+
+    /// Terminates the reading of the grid (whose number of rows must be equal to
+    /// the height of the grid) and returns the remaining rings.
+    pub fn finish(mut self) -> Result<Vec<BandRing>> {
+        let rings = self.finish_raw()?;
+        Ok(self.to_band_rings(rings))
+    }
+
+    /// Returns the number of paths that are currently open (for all the bands),
+    /// i.e. the number of paths that cross the last rows read.
+    pub fn open_paths(&self) -> usize {
+        self.paths.iter().map(|paths| paths.open.len()).sum()
+    }
+
+    pub(crate) fn push_strip_raw<T: GridValue>(
+        &mut self,
+        strip: &[T],
+    ) -> Result<Vec<(usize, Vec<Point<f64>>)>> {
+        let width = self.builder.width;
+        let n_rows = strip.len() / width;
+        if n_rows * width != strip.len() || self.rows + n_rows > self.builder.height {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+
+        let mask = self
+            .builder
+            .mask
+            .as_deref()
+            .map(|mask| &mask[self.rows * width..(self.rows + n_rows) * width]);
+        let grid = BorrowedGrid::new(strip, width, n_rows)
+            .with_nodata(self.builder.nodata)
+            .with_mask(mask);
+        for j in 0..n_rows {
+            let start = self.buffer.len();
+            self.buffer.extend((0..width).map(|i| grid.value((i, j))));
+            let row = &self.buffer[start..];
+            let frame = if self.rows + j == 0 || self.rows + j + 1 == self.builder.height {
+                row
+            } else {
+                &[row[0], row[width - 1]][..]
+            };
+            for ((opt, _, _), paths) in self.bands.iter().zip(self.paths.iter_mut()) {
+                paths.frame_in_band &= frame.iter().all(|&v| {
+                    if v.is_nan() {
+                        opt.nodata_boundary == NoDataBoundary::DropCell
+                    } else {
+                        v >= opt.min_v && v <= opt.max_v
+                    }
+                });
+            }
+        }
+        self.rows += n_rows;
+
+        let mut rings = Vec::new();
+        self.process_cells(false, &mut rings)?;
+        Ok(rings)
+    }
+
+    pub(crate) fn finish_raw(&mut self) -> Result<Vec<(usize, Vec<Point<f64>>)>> {
+        if self.rows != self.builder.height {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+
+        let mut rings = Vec::new();
+        self.process_cells(true, &mut rings)?;
+        if self.open_paths() != 0 {
+            return Err(new_error(ErrorKind::PolygonReconstructionError));
+        }
+
+        let (cols, rows) = (self.builder.width - 1, self.builder.height - 1);
+        for (band, paths) in self.paths.iter().enumerate() {
+            if paths.frame_in_band && !paths.walked_frame {
+                rings.push((band, frame_ring(cols, rows)));
+            }
+        }
+        Ok(rings)
+    }
+
+    fn to_band_rings(&self, rings: Vec<(usize, Vec<Point<f64>>)>) -> Vec<BandRing> {
+        rings
+            .into_iter()
+            .filter_map(|(band, mut points)| {
+                points.dedup();
+                if points.len() < 4 {
+                    return None;
+                }
+                // Rings are built with the band on their right,
+                // so the orientation of the ring (in grid coordinates) tells if it is a hole
+                let grid_area = area(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+                if grid_area == 0. {
+                    return None;
+                }
+                let is_exterior = grid_area > 0.;
+                self.builder.transform_points(&mut points);
+                let mut ring = LineString::from(points);
+                if self.builder.ensure_rings_orientation
+                    && !is_winding_correct(area(&ring.0), is_exterior)
+                {
+                    ring.0.reverse();
+                }
+                let (_, min_v, max_v) = self.bands[band];
+                Some(BandRing {
+                    band,
+                    min_v,
+                    max_v,
+                    is_exterior,
+                    ring,
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the boundaries of the bands in the rows of cells whose neighbours
+    /// have been read (or in all the remaining rows, at the end of the grid).
+    fn process_cells(
+        &mut self,
+        last: bool,
+        rings: &mut Vec<(usize, Vec<Point<f64>>)>,
+    ) -> Result<()> {
+        let end = if last {
+            self.rows - 1
+        } else {
+            self.rows.saturating_sub(2)
+        };
+        if end <= self.next_cell_row {
+            return Ok(());
+        }
+
+        let width = self.builder.width;
+        let buffer = std::mem::take(&mut self.buffer);
+        let grid = BorrowedGrid::new(&buffer, width, buffer.len() / width);
+        let tree = self.builder.use_quad_tree.then(|| QuadTree::new(&grid));
+        let cell_rows = self.next_cell_row..end;
+
+        for band in 0..self.bands.len() {
+            let cells = match &tree {
+                Some(tree) => tree
+                    .cells_in_band(self.bands[band].0.min_v, self.bands[band].0.max_v)
+                    .into_iter()
+                    .map(|(x, y)| (x, y + self.first_row))
+                    .filter(|(_, y)| cell_rows.contains(y))
+                    .collect(),
+                None => cell_rows
+                    .clone()
+                    .flat_map(|y| (0..width - 1).map(move |x| (x, y)))
+                    .collect::<Vec<_>>(),
+            };
+            for (x, y) in cells {
+                if let Some(cell) = prepare_cell(x, y - self.first_row, &grid, &self.bands[band].0)?
+                {
+                    self.cell_segments(band, x, y, &cell, &grid, rings);
+                }
+            }
+        }
+
+        // Keep the row below the next cells to process
+        self.next_cell_row = end;
+        let first_row = end - 1;
+        self.buffer = buffer;
+        self.buffer.drain(..(first_row - self.first_row) * width);
+        self.first_row = first_row;
+        Ok(())
+    }
+
+    /// What the sides of a valid cell follow when they are next to the cell `(x, y)`,
+    /// if it is outside of the grid or dropped (the grid holding the rows read
+    /// from the row `first_row`).
+    fn boundary(
+        &self,
+        x: i64,
+        y: i64,
+        grid: &BorrowedGrid<f64>,
+        nodata_boundary: NoDataBoundary,
+    ) -> Option<Segment> {
+        let (width, height) = (self.builder.width as i64, self.builder.height as i64);
+        if x < 0 || y < 0 || x + 1 >= width || y + 1 >= height {
+            return Some(Segment::Frame);
+        }
+        let (x, y) = (x as usize, y as usize - self.first_row);
+        let dropped = nodata_boundary == NoDataBoundary::DropCell
+            && [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+                .into_iter()
+                .any(|p| grid.value(p).is_nan());
+        dropped.then_some(Segment::Dropped)
+    }
+
+    /// Adds the segments of the boundary of a band in the cell `(x, y)`:
+    /// the edges of the cell and the parts of its sides that are inside the band,
+    /// when the neighbour cell is outside of the grid or dropped.
+    fn cell_segments(
+        &mut self,
+        band: usize,
+        x: usize,
+        y: usize,
+        cell: &Cell,
+        grid: &BorrowedGrid<f64>,
+        rings: &mut Vec<(usize, Vec<Point<f64>>)>,
+    ) {
+        let offset = |p: &Pt| Point::new(p.0 + x as f64, p.1 + y as f64);
+        for (enter, edge) in &cell.edges {
+            let mut points = vec![offset(&edge.path[0])];
+            points.extend(edge.via.as_ref().map(offset));
+            points.push(offset(&edge.path[1]));
+            let start = crossing_key(x, y, enter);
+            let end = crossing_key(x, y, &exit_type(&edge.move_info.enter));
+            let order = START_ORDER.iter().position(|e| e == enter).unwrap();
+            self.add_path(
+                band,
+                start,
+                end,
+                &points,
+                Segment::Cell((x, y, order)),
+                rings,
+            );
+        }
+
+        let opt = &self.bands[band].0;
+        let (min_v, max_v, nodata_boundary) = (opt.min_v, opt.max_v, opt.nodata_boundary);
+        let in_band = |(i, j): (usize, usize)| {
+            let v = [[cell.x0, cell.x3], [cell.x1, cell.x2]][i][j];
+            !v.is_nan() && v >= min_v && v <= max_v
+        };
+        for (neighbour, a, b, entries) in SIDES {
+            let (nx, ny) = (x as i64 + neighbour.0 as i64, y as i64 + neighbour.1 as i64);
+            let Some(segment) = self.boundary(nx, ny, grid, nodata_boundary) else {
+                continue;
+            };
+
+            // The crossing points on the side, ordered from `a` to `b`
+            // (the band being inside between the paths leaving and entering the cell)
+            let position = |p: &Pt| match neighbour {
+                (1, 0) => 1. - p.1,
+                (0, -1) => 1. - p.0,
+                (-1, 0) => p.1,
+                _ => p.0,
+            };
+            let mut crossings = cell
+                .edges
+                .iter()
+                .filter_map(|(enter, edge)| {
+                    if entries.contains(enter) {
+                        Some((
+                            position(&edge.path[0]),
+                            true,
+                            crossing_key(x, y, enter),
+                            offset(&edge.path[0]),
+                        ))
+                    } else if (edge.move_info.x, edge.move_info.y) == neighbour {
+                        let exit = exit_type(&edge.move_info.enter);
+                        Some((
+                            position(&edge.path[1]),
+                            false,
+                            crossing_key(x, y, &exit),
+                            offset(&edge.path[1]),
+                        ))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            crossings.sort_by(|c1, c2| c1.0.total_cmp(&c2.0));
+
+            let node = |(i, j): (usize, usize)| {
+                let key = (2 * (x + i), 2 * (y + j), NODE);
+                (key, Point::new((x + i) as f64, (y + j) as f64))
+            };
+            // The side starts inside the band if the first crossing point enters the cell
+            // (unless a path also leaves the cell at this point)
+            let mut inside = in_band(a);
+            if let Some(&(position, is_entry, _, _)) = crossings.first() {
+                if crossings
+                    .iter()
+                    .take_while(|c| c.0 == position)
+                    .all(|c| c.1 == is_entry)
+                {
+                    inside = is_entry;
+                }
+            }
+            let mut from = inside.then(|| node(a));
+            for i in 0..crossings.len() {
+                // A path leaving the cell and a path entering it can cross the side
+                // at the same point: take first the one closing the current part
+                if crossings[i].1 != inside {
+                    if let Some(j) = (i + 1..crossings.len())
+                        .take_while(|&j| crossings[j].0 == crossings[i].0)
+                        .find(|&j| crossings[j].1 == inside)
+                    {
+                        crossings.swap(i, j);
+                    }
+                }
+                let (_, is_entry, key, point) = crossings[i];
+                if is_entry {
+                    if let Some((start, start_point)) = from.take() {
+                        self.add_path(band, start, key, &[start_point, point], segment, rings);
+                    }
+                    inside = false;
+                } else {
+                    from = Some((key, point));
+                    inside = true;
+                }
+            }
+            if let Some((start, start_point)) = from {
+                let (end, end_point) = node(b);
+                self.add_path(band, start, end, &[start_point, end_point], segment, rings);
+            }
+        }
+    }
+
+    fn add_path(
+        &mut self,
+        band: usize,
+        start: PointKey,
+        end: PointKey,
+        points: &[Point<f64>],
+        segment: Segment,
+        rings: &mut Vec<(usize, Vec<Point<f64>>)>,
+    ) {
+        let paths = &mut self.paths[band];
+        if let Some(ring) = paths.open.add_path(start, end, points, segment) {
+            if ring.crosses {
+                paths.walked_frame |= ring.frame;
+                rings.push((band, ring.into_ring()));
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::isobands::{Band, ContourBuilder};
+use geo_types::LineString;
+use std::fmt::Write;
+
+/// A color ramp, defined by colors at given positions (between 0 and 1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, [u8; 3])>,
+}
+
+impl ColorRamp {
+    /// Constructs a new color ramp from its stops, given as
+    /// (position between 0 and 1, RGB color) pairs.
+    pub fn new(stops: &[(f64, [u8; 3])]) -> Self {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp { stops }
+    }
+
+    /// Constructs a new color ramp from evenly spaced colors.
+    pub fn from_colors(colors: &[[u8; 3]]) -> Self {
+        let n = colors.len().saturating_sub(1).max(1) as f64;
+        ColorRamp {
+            stops: colors
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| (i as f64 / n, color))
+                .collect(),
+        }
+    }
+
+    /// The "viridis" color ramp (from dark blue to yellow).
+    pub fn viridis() -> Self {
+        ColorRamp::from_colors(&[
+            [68, 1, 84],
+            [59, 82, 139],
+            [33, 145, 140],
+            [94, 201, 98],
+            [253, 231, 37],
+        ])
+    }
+
+    /// A color ramp from white to black.
+    pub fn greys() -> Self {
+        ColorRamp::from_colors(&[[255, 255, 255], [0, 0, 0]])
+    }
+
+    /// Get the color at the given position (between 0 and 1),
+    /// interpolating linearly between the stops.
+    pub fn color(&self, t: f64) -> [u8; 3] {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return [0, 0, 0],
+        };
+        if t.is_nan() || t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let i = self.stops.iter().position(|stop| stop.0 > t).unwrap();
+        let ((t0, c0), (t1, c1)) = (self.stops[i - 1], self.stops[i]);
+        let f = (t - t0) / (t1 - t0);
+        [0, 1, 2].map(|k| (c0[k] as f64 + f * (c1[k] as f64 - c0[k] as f64)).round() as u8)
+    }
+}
+
+impl Default for ColorRamp {
+    fn default() -> Self {
+        ColorRamp::viridis()
+    }
+}
+
+/// SVG renderer, using builder pattern, to draw a `Slice` of [`Band`]
+/// as a SVG document (mostly intended for quick visual checks).
+///
+/// Each band is drawn as a `<path>` (using the `evenodd` fill rule, so that the holes
+/// are respected) whose color is taken from the color ramp, according to the middle
+/// of its `min_v` / `max_v` interval (relatively to the range of the finite values of all
+/// the bands, the open bands being drawn with the colors at the ends of the ramp).
+pub struct SvgRenderer {
+    /// The width of the document, in pixels
+    width: f64,
+    /// The color ramp used to fill the bands
+    color_ramp: ColorRamp,
+    /// Whether to draw a legend
+    legend: bool,
+    /// The color of the stroke of the bands (if any)
+    stroke: Option<String>,
+    /// The extent to draw (`[min_x, min_y, max_x, max_y]`)
+    extent: Option<[f64; 4]>,
+    /// Whether the y axis is pointing up (as in geographic coordinates)
+    y_up: bool,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        SvgRenderer::new()
+    }
+}
+
+impl SvgRenderer {
+    /// Constructs a new SVG renderer.
+    ///
+    /// By default, `width` is set to `800`, the bands are filled using the
+    /// `viridis` color ramp, without stroke nor legend, and the extent is the one
+    /// of the bands (with the y axis pointing down, as in grid coordinates).
+    /// This can be changed using the corresponding methods.
+    pub fn new() -> Self {
+        SvgRenderer {
+            width: 800.,
+            color_ramp: ColorRamp::default(),
+            legend: false,
+            stroke: None,
+            extent: None,
+            y_up: false,
+        }
+    }
+
+    /// Sets the width of the document, in pixels (its height is computed
+    /// to preserve the aspect ratio of the extent).
+    pub fn width(mut self, width: impl Into<f64>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the color ramp used to fill the bands.
+    pub fn color_ramp(mut self, color_ramp: ColorRamp) -> Self {
+        self.color_ramp = color_ramp;
+        self
+    }
+
+    /// Sets whether to draw a legend (on the right of the bands).
+    pub fn legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Sets the color of the stroke of the bands (any SVG color, such as `"#333"`).
+    pub fn stroke(mut self, stroke: impl Into<String>) -> Self {
+        self.stroke = Some(stroke.into());
+        self
+    }
+
+    /// Sets the extent to draw (`[min_x, min_y, max_x, max_y]`).
+    pub fn extent(mut self, extent: [f64; 4]) -> Self {
+        self.extent = Some(extent);
+        self
+    }
+
+    /// Sets whether the y axis is pointing up (as in geographic coordinates).
+    pub fn y_up(mut self, y_up: bool) -> Self {
+        self.y_up = y_up;
+        self
+    }
+
+    /// Sets the extent (and the direction of the y axis)
+    /// from the grid described by the given [`ContourBuilder`].
+    pub fn grid(mut self, builder: &ContourBuilder) -> Self {
+        let (extent, y_up) = builder.grid_extent();
+        self.extent = Some(extent);
+        self.y_up = y_up;
+        self
+    }
+
+    /// Renders the given bands as a SVG document.
+    pub fn render(&self, bands: &[Band]) -> String {
+        let [min_x, min_y, max_x, max_y] = self.extent.unwrap_or_else(|| bands_extent(bands));
+        let extent_width = if max_x > min_x { max_x - min_x } else { 1. };
+        let extent_height = if max_y > min_y { max_y - min_y } else { 1. };
+        let height = (self.width * extent_height / extent_width).round();
+        let view_box_y = if self.y_up { -max_y } else { min_y };
+
+        // The open bands (with an infinite bound) are clamped to the finite
+        // bounds of the bands, so that they are colored at the ends of the ramp
+        let finite_v = || {
+            bands
+                .iter()
+                .flat_map(|b| [b.min_v, b.max_v])
+                .filter(|v| v.is_finite())
+        };
+        let min_v = finite_v().fold(f64::INFINITY, f64::min);
+        let max_v = finite_v().fold(f64::NEG_INFINITY, f64::max);
+        let band_color = |band: &Band| {
+            let t = if max_v > min_v {
+                let (low, high) = (band.min_v.max(min_v), band.max_v.min(max_v));
+                ((low + high) / 2. - min_v) / (max_v - min_v)
+            } else {
+                0.5
+            };
+            let [r, g, b] = self.color_ramp.color(t);
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        };
+
+        // The legend is drawn on the right of the bands (with one line per band)
+        let (legend_width, legend_height) = if self.legend {
+            (200., bands.len() as f64 * 20. + 20.)
+        } else {
+            (0., 0.)
+        };
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width + legend_width,
+            h = height.max(legend_height),
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<svg width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            self.width, height, min_x, view_box_y, extent_width, extent_height,
+        )
+        .unwrap();
+
+        for band in bands {
+            let mut d = String::new();
+            for polygon in band.geometry().0.iter() {
+                for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors().iter()) {
+                    self.write_ring(&mut d, ring);
+                }
+            }
+            if d.is_empty() {
+                continue;
+            }
+            write!(
+                svg,
+                r#"<path d="{}" fill="{}" fill-rule="evenodd""#,
+                d.trim_end(),
+                band_color(band),
+            )
+            .unwrap();
+            if let Some(stroke) = &self.stroke {
+                write!(
+                    svg,
+                    r#" stroke="{}" vector-effect="non-scaling-stroke""#,
+                    escape(stroke)
+                )
+                .unwrap();
+            }
+            writeln!(
+                svg,
+                r#" data-min-v="{}" data-max-v="{}"/>"#,
+                band.min_v, band.max_v
+            )
+            .unwrap();
+        }
+        writeln!(svg, "</svg>").unwrap();
+
+        if self.legend {
+            writeln!(
+                svg,
+                r#"<g transform="translate({} 10)" font-family="sans-serif" font-size="12">"#,
+                self.width + 10.
+            )
+            .unwrap();
+            for (i, band) in bands.iter().enumerate() {
+                let y = i as f64 * 20.;
+                writeln!(
+                    svg,
+                    r#"<rect x="0" y="{}" width="20" height="14" fill="{}"/><text x="28" y="{}">{} – {}</text>"#,
+                    y,
+                    band_color(band),
+                    y + 11.,
+                    band.min_v,
+                    band.max_v,
+                )
+                .unwrap();
+            }
+            writeln!(svg, "</g>").unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn write_ring(&self, d: &mut String, ring: &LineString<f64>) {
+        // The closing point is replaced by the 'Z' command
+        let n = if ring.is_closed() {
+            ring.0.len().saturating_sub(1)
+        } else {
+            ring.0.len()
+        };
+        for (i, coord) in ring.0[..n].iter().enumerate() {
+            let y = if self.y_up { -coord.y } else { coord.y };
+            write!(d, "{}{} {} ", if i == 0 { 'M' } else { 'L' }, coord.x, y).unwrap();
+        }
+        if n > 0 {
+            d.push_str("Z ");
+        }
+    }
+}
+
+/// Escape the characters that are not allowed in a XML attribute value.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Compute the extent (`[min_x, min_y, max_x, max_y]`) of the bands.
+fn bands_extent(bands: &[Band]) -> [f64; 4] {
+    let mut extent = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    for coord in bands
+        .iter()
+        .flat_map(|band| band.geometry().0.iter())
+        .flat_map(|polygon| polygon.exterior().0.iter())
+    {
+        extent[0] = extent[0].min(coord.x);
+        extent[1] = extent[1].min(coord.y);
+        extent[2] = extent[2].max(coord.x);
+        extent[3] = extent[3].max(coord.y);
+    }
+    if extent[0] > extent[2] {
+        [0., 0., 1., 1.]
+    } else {
+        extent
+    }
+}
@@ -0,0 +1,305 @@
+use crate::errors::{new_error, ErrorKind, Result};
+use crate::grid::GridValue;
+
+/// The maximum number of values used to compute the Jenks natural breaks
+/// (on larger grids, the breaks are computed on evenly spaced quantiles of the values).
+const JENKS_MAX_VALUES: usize = 2000;
+
+/// The maximum proportion of the values that the head can contain to continue
+/// the head/tail breaks classification.
+const HEAD_TAIL_RATIO: f64 = 0.4;
+
+/// A classification scheme used to derive thresholds from the values of the grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Classification {
+    /// Classes of equal width (with the given number of classes)
+    EqualInterval(usize),
+    /// Classes containing the same number of values (with the given number of classes)
+    Quantile(usize),
+    /// Jenks natural breaks, minimizing the variance inside the classes
+    /// (with the given number of classes)
+    Jenks(usize),
+    /// Classes whose width is the given fraction of the standard deviation
+    /// of the values, one of the thresholds being the mean of the values
+    StandardDeviation(f64),
+    /// Classes whose width follows a geometric progression
+    /// (with the given number of classes, the values must be strictly positive)
+    Geometric(usize),
+    /// Head/tail breaks, suited to heavy-tailed distributions: the values are
+    /// split around their mean, and the head (the values above the mean) is split again
+    /// as long as it contains less than 40% of the values
+    HeadTail,
+    /// Round ("nice") thresholds, whose step is 1, 2, 2.5 or 5 times a power of 10,
+    /// chosen to get approximately the given number of classes
+    /// (the first and last thresholds are the minimum and maximum values rounded
+    /// to a multiple of the step)
+    Nice(usize),
+}
+
+/// Computes thresholds for the given data, according to the given classification
+/// scheme (the first threshold is the minimum value and the last one is the maximum value,
+/// or these values rounded outward for [`Classification::Nice`],
+/// so that the bands computed from these thresholds contain all the values).
+///
+/// NaN and infinite values, and values equal to `nodata` (if any, compared
+/// in the type of the values, as in the grids), are ignored.
+pub fn thresholds<T: GridValue>(
+    data: &[T],
+    classification: Classification,
+    nodata: Option<f64>,
+) -> Result<Vec<f64>> {
+    let nodata = nodata.map(T::from_f64);
+    _thresholds(
+        data.iter()
+            .filter(|&&v| Some(v) != nodata)
+            .map(|v| v.to_f64()),
+        classification,
+    )
+}
+
+/// Computes round ("nice") thresholds covering the range of the given data,
+/// with a step of 1, 2, 2.5 or 5 times a power of 10, chosen to get approximately
+/// `approx_count` bands (see [`Classification::Nice`]).
+///
+/// NaN and infinite values are ignored.
+pub fn nice_thresholds<T: GridValue>(data: &[T], approx_count: usize) -> Result<Vec<f64>> {
+    _thresholds(
+        data.iter().map(|v| v.to_f64()),
+        Classification::Nice(approx_count),
+    )
+}
+
+pub(crate) fn _thresholds(
+    values: impl Iterator<Item = f64>,
+    classification: Classification,
+) -> Result<Vec<f64>> {
+    let values = values.filter(|v| v.is_finite());
+
+    let breaks = match classification {
+        Classification::EqualInterval(n)
+        | Classification::Geometric(n)
+        | Classification::Nice(n) => {
+            if n == 0 {
+                return Err(new_error(ErrorKind::BadClassification(
+                    "the number of classes must be positive",
+                )));
+            }
+            let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+            if min > max {
+                return Err(new_error(ErrorKind::BadData));
+            }
+            if let Classification::Nice(_) = classification {
+                nice_breaks(min, max, n)
+            } else if let Classification::Geometric(_) = classification {
+                if min <= 0. {
+                    return Err(new_error(ErrorKind::BadClassification(
+                        "geometric classification requires strictly positive values",
+                    )));
+                }
+                let ratio = (max / min).powf(1. / n as f64);
+                let mut breaks = (0..n)
+                    .map(|i| min * ratio.powi(i as i32))
+                    .collect::<Vec<_>>();
+                breaks.push(max);
+                breaks
+            } else {
+                let step = (max - min) / n as f64;
+                let mut breaks = (0..n).map(|i| min + i as f64 * step).collect::<Vec<_>>();
+                breaks.push(max);
+                breaks
+            }
+        }
+        Classification::Quantile(n) | Classification::Jenks(n) => {
+            if n == 0 {
+                return Err(new_error(ErrorKind::BadClassification(
+                    "the number of classes must be positive",
+                )));
+            }
+            let mut values = values.collect::<Vec<f64>>();
+            if values.is_empty() {
+                return Err(new_error(ErrorKind::BadData));
+            }
+            values.sort_unstable_by(|a, b| a.total_cmp(b));
+            if let Classification::Jenks(_) = classification {
+                jenks(&values, n)
+            } else {
+                let len = values.len();
+                let mut breaks = (0..n).map(|i| values[i * len / n]).collect::<Vec<_>>();
+                breaks.push(values[len - 1]);
+                breaks
+            }
+        }
+        Classification::StandardDeviation(fraction) => {
+            if fraction.is_nan() || fraction <= 0. {
+                return Err(new_error(ErrorKind::BadClassification(
+                    "the fraction of standard deviation must be positive",
+                )));
+            }
+            let (mut n, mut sum, mut sum_sq) = (0usize, 0., 0.);
+            let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+            for v in values {
+                n += 1;
+                sum += v;
+                sum_sq += v * v;
+                min = min.min(v);
+                max = max.max(v);
+            }
+            if n == 0 {
+                return Err(new_error(ErrorKind::BadData));
+            }
+            let mean = sum / n as f64;
+            let step = (sum_sq / n as f64 - mean * mean).max(0.).sqrt() * fraction;
+            let mut breaks = vec![min];
+            if step > 0. {
+                let first = -((mean - min) / step).floor();
+                let last = ((max - mean) / step).floor();
+                breaks.extend(
+                    (first as i64..=last as i64)
+                        .map(|i| mean + i as f64 * step)
+                        .filter(|&b| b > min && b < max),
+                );
+            }
+            breaks.push(max);
+            breaks
+        }
+        Classification::HeadTail => {
+            let mut head = values.collect::<Vec<f64>>();
+            if head.is_empty() {
+                return Err(new_error(ErrorKind::BadData));
+            }
+            let min = head.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = head.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let mut breaks = vec![min];
+            loop {
+                let mean = head.iter().sum::<f64>() / head.len() as f64;
+                if mean <= min || mean >= max {
+                    break;
+                }
+                breaks.push(mean);
+                let previous_len = head.len();
+                head.retain(|&v| v > mean);
+                if head.len() <= 1 || head.len() as f64 / previous_len as f64 > HEAD_TAIL_RATIO {
+                    break;
+                }
+            }
+            breaks.push(max);
+            breaks
+        }
+    };
+
+    Ok(finish_breaks(breaks))
+}
+
+/// Compute the multiples of a round step (1, 2, 2.5 or 5 times a power of 10)
+/// covering [min, max], the step being chosen to get approximately `n` intervals.
+fn nice_breaks(min: f64, max: f64, n: usize) -> Vec<f64> {
+    if max <= min {
+        return vec![min, max];
+    }
+    let raw_step = (max - min) / n as f64;
+    let power = 10f64.powi(raw_step.log10().floor() as i32);
+    // Choose the closest candidate (on a logarithmic scale) to the raw step
+    let factor = [1., 2., 2.5, 5., 10.]
+        .into_iter()
+        .min_by(|a, b| {
+            (a * power / raw_step)
+                .ln()
+                .abs()
+                .total_cmp(&(b * power / raw_step).ln().abs())
+        })
+        .unwrap();
+    let step = factor * power;
+
+    // The thresholds are computed from integers, dividing by the inverse of the step
+    // when it is lower than 1 (i.e. 0.1 * 3 is computed as 3 / 10), to avoid
+    // floating point artifacts such as 0.30000000000000004
+    let inverse = (1. / step).round();
+    let value = |k: f64| if step < 1. { k / inverse } else { k * step };
+    let (start, end) = ((min / step).floor(), (max / step).ceil());
+    let (start, end) = (
+        if value(start + 1.) <= min {
+            start + 1.
+        } else {
+            start
+        },
+        if value(end - 1.) >= max {
+            end - 1.
+        } else {
+            end
+        },
+    );
+    (start as i64..=end as i64)
+        .map(|k| value(k as f64))
+        .collect()
+}
+
+/// Ensure that the thresholds are strictly increasing.
+fn finish_breaks(mut breaks: Vec<f64>) -> Vec<f64> {
+    breaks.sort_unstable_by(|a, b| a.total_cmp(b));
+    breaks.dedup();
+    if breaks.len() == 1 {
+        // All the values are equal: a single band [v, v] contains them
+        breaks.push(breaks[0]);
+    }
+    breaks
+}
+
+/// Compute the Jenks natural breaks of the (sorted) values
+/// (using Fisher's dynamic programming algorithm),
+/// returning the first value of each class and the maximum value.
+fn jenks(values: &[f64], n_classes: usize) -> Vec<f64> {
+    let sample = if values.len() > JENKS_MAX_VALUES {
+        (0..JENKS_MAX_VALUES)
+            .map(|i| values[i * (values.len() - 1) / (JENKS_MAX_VALUES - 1)])
+            .collect::<Vec<_>>()
+    } else {
+        values.to_vec()
+    };
+    let n = sample.len();
+    let k = n_classes.min(n);
+
+    // lower_class_limits[l][j] is the (1-based) index of the first value of the last class
+    // for the best classification of the first l values in j classes
+    let mut lower_class_limits = vec![vec![0usize; k + 1]; n + 1];
+    let mut variance_combinations = vec![vec![f64::INFINITY; k + 1]; n + 1];
+    for j in 1..=k {
+        lower_class_limits[1][j] = 1;
+        variance_combinations[1][j] = 0.;
+    }
+
+    for l in 2..=n {
+        let (mut sum, mut sum_sq, mut w) = (0., 0., 0.);
+        let mut variance = 0.;
+        for m in 1..=l {
+            let lower_class_limit = l - m + 1;
+            let v = sample[lower_class_limit - 1];
+            w += 1.;
+            sum += v;
+            sum_sq += v * v;
+            variance = sum_sq - (sum * sum) / w;
+            let i = lower_class_limit - 1;
+            if i != 0 {
+                for j in 2..=k {
+                    if variance_combinations[l][j] >= variance + variance_combinations[i][j - 1] {
+                        lower_class_limits[l][j] = lower_class_limit;
+                        variance_combinations[l][j] = variance + variance_combinations[i][j - 1];
+                    }
+                }
+            }
+        }
+        lower_class_limits[l][1] = 1;
+        variance_combinations[l][1] = variance;
+    }
+
+    let mut breaks = vec![sample[0]; k + 1];
+    breaks[k] = sample[n - 1];
+    let mut l = n;
+    for j in (2..=k).rev() {
+        let lower_class_limit = lower_class_limits[l][j];
+        breaks[j - 1] = sample[lower_class_limit - 1];
+        l = lower_class_limit - 1;
+    }
+    breaks
+}
@@ -0,0 +1,195 @@
+use crate::isobands::{Band, BandRaw};
+use crate::topology::Topology;
+use geo_types::{Coord, Point};
+use serde_json::{json, Value};
+
+/// TopoJSON writer, using builder pattern, to encode a `Slice` of [`Band`]
+/// as a TopoJSON topology.
+///
+/// The coordinates are quantized and the rings are cut into arcs at the points where
+/// they meet other rings, so that the boundaries shared by adjacent bands are only
+/// stored once, as arcs referenced by the polygons of both bands.
+/// Each band is encoded as a `MultiPolygon` geometry (of a `GeometryCollection` object)
+/// with its `min_v` and `max_v` values as properties.
+pub struct TopoJsonWriter {
+    /// The number of distinct values on each axis of the quantized coordinates
+    quantization: u32,
+    /// The name of the object containing the bands
+    object_name: String,
+}
+
+impl Default for TopoJsonWriter {
+    fn default() -> Self {
+        TopoJsonWriter::new()
+    }
+}
+
+impl TopoJsonWriter {
+    /// Constructs a new TopoJSON writer.
+    ///
+    /// By default, `quantization` is set to `1e6` and `object_name` to `"isobands"`.
+    /// This can be changed using the corresponding methods.
+    pub fn new() -> Self {
+        TopoJsonWriter {
+            quantization: 1_000_000,
+            object_name: "isobands".to_string(),
+        }
+    }
+
+    /// Sets the number of distinct values on each axis of the quantized coordinates
+    /// (at least 2), i.e. the precision of the coordinates.
+    ///
+    /// Note that the quantization also merges the boundaries shared by adjacent bands,
+    /// which are computed separately for each band and may differ by a tiny amount.
+    pub fn quantization(mut self, quantization: u32) -> Self {
+        self.quantization = quantization.max(2);
+        self
+    }
+
+    /// Sets the name of the object containing the bands.
+    pub fn object_name(mut self, object_name: impl Into<String>) -> Self {
+        self.object_name = object_name.into();
+        self
+    }
+
+    /// Encodes the given bands as a TopoJSON topology.
+    ///
+    /// To get a string representation, call `to_string()` on the result.
+    pub fn write(&self, bands: &[Band]) -> Value {
+        let [min_x, min_y, max_x, max_y] = bands_bbox(bands);
+        let n = (self.quantization - 1) as f64;
+        let kx = if max_x > min_x {
+            (max_x - min_x) / n
+        } else {
+            1.
+        };
+        let ky = if max_y > min_y {
+            (max_y - min_y) / n
+        } else {
+            1.
+        };
+        let quantize =
+            |c: &Coord<f64>| Point::new(((c.x - min_x) / kx).round(), ((c.y - min_y) / ky).round());
+
+        // Quantize the rings, removing the positions that are merged by the quantization
+        // (and the rings, or the polygons, that collapse)
+        let polygons = bands
+            .iter()
+            .map(|band| {
+                band.geometry()
+                    .0
+                    .iter()
+                    .filter_map(|polygon| {
+                        let mut rings = std::iter::once(polygon.exterior())
+                            .chain(polygon.interiors().iter())
+                            .map(|ring| {
+                                let mut positions = ring.0.iter().map(quantize).collect::<Vec<_>>();
+                                positions.dedup();
+                                positions
+                            });
+                        let exterior = rings.next().filter(|ring| ring.len() >= 4)?;
+                        Some(
+                            std::iter::once(exterior)
+                                .chain(rings.filter(|ring| ring.len() >= 4))
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // The rings of each band are given in order to the topology (which keeps them
+        // all, as they are closed and not degenerated), and grouped back into polygons
+        let raw_bands = polygons
+            .iter()
+            .zip(bands.iter())
+            .map(|(polygons, band)| {
+                let rings = polygons.iter().flatten().cloned().collect::<Vec<_>>();
+                (rings, band.min_v, band.max_v)
+            })
+            .collect::<Vec<BandRaw>>();
+        let topology = Topology::new(&raw_bands);
+        let geometries = bands
+            .iter()
+            .zip(polygons.iter())
+            .zip(topology.rings.iter())
+            .map(|((band, polygons), rings)| {
+                let mut rings = rings.iter().map(|ring| {
+                    ring.iter()
+                        .map(|&(i, reversed)| if reversed { !(i as i64) } else { i as i64 })
+                        .collect::<Vec<_>>()
+                });
+                let polygons = polygons
+                    .iter()
+                    .map(|polygon| rings.by_ref().take(polygon.len()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>();
+                json!({
+                    "type": "MultiPolygon",
+                    "arcs": polygons,
+                    "properties": {
+                        "min_v": band.min_v,
+                        "max_v": band.max_v,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // The arcs are delta-encoded
+        let arcs = topology
+            .arcs
+            .iter()
+            .map(|arc| {
+                let mut previous = (0, 0);
+                arc.iter()
+                    .map(|&i| {
+                        let (x, y) = (topology.points[i].x() as i64, topology.points[i].y() as i64);
+                        let delta = [x - previous.0, y - previous.1];
+                        previous = (x, y);
+                        delta
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "type": "Topology",
+            "bbox": [min_x, min_y, max_x, max_y],
+            "transform": {
+                "scale": [kx, ky],
+                "translate": [min_x, min_y],
+            },
+            "objects": {
+                &self.object_name: {
+                    "type": "GeometryCollection",
+                    "geometries": geometries,
+                },
+            },
+            "arcs": arcs,
+        })
+    }
+}
+
+/// Compute the bounding box (`[min_x, min_y, max_x, max_y]`) of the bands.
+fn bands_bbox(bands: &[Band]) -> [f64; 4] {
+    let mut bbox = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    for coord in bands
+        .iter()
+        .flat_map(|band| band.geometry().0.iter())
+        .flat_map(|polygon| polygon.exterior().0.iter())
+    {
+        bbox[0] = bbox[0].min(coord.x);
+        bbox[1] = bbox[1].min(coord.y);
+        bbox[2] = bbox[2].max(coord.x);
+        bbox[3] = bbox[3].max(coord.y);
+    }
+    if bbox[0] > bbox[2] {
+        [0., 0., 0., 0.]
+    } else {
+        bbox
+    }
+}
@@ -0,0 +1,170 @@
+use crate::isobands::BandRaw;
+use geo_types::Point;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Distance under which two points of the rings are considered to be the same point
+/// (the boundaries shared by adjacent bands are computed separately for each band
+/// and may differ by a tiny amount).
+const SNAP_DISTANCE: f64 = 1e-9;
+
+/// The rings of the bands (in grid coordinates), cut into arcs at the points where
+/// they meet other rings, so that each boundary shared by two adjacent bands
+/// is a single arc, used by the rings of both bands.
+pub(crate) struct Topology {
+    /// The (deduplicated) points of the rings
+    pub points: Vec<Point<f64>>,
+    /// The points where the rings are cut into arcs
+    pub junctions: FxHashSet<usize>,
+    /// The arcs shared by the rings (i.e. the parts of the rings between
+    /// two junctions, or whole rings), as the indices of their points
+    pub arcs: Vec<Vec<usize>>,
+    /// The rings of each band, as the arcs composing them (and whether they are reversed)
+    pub rings: Vec<Vec<Vec<(usize, bool)>>>,
+}
+
+impl Topology {
+    pub fn new(bands: &[BandRaw]) -> Self {
+        // Merge the points that are at the same place
+        let mut points: Vec<Point<f64>> = Vec::new();
+        let mut buckets: FxHashMap<(i64, i64), Vec<usize>> = FxHashMap::default();
+        let mut point_id = |p: &Point<f64>| -> usize {
+            let key = (
+                (p.x() / SNAP_DISTANCE).floor() as i64,
+                (p.y() / SNAP_DISTANCE).floor() as i64,
+            );
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if let Some(ids) = buckets.get(&(key.0 + dx, key.1 + dy)) {
+                        if let Some(&id) = ids.iter().find(|&&id| {
+                            (points[id].x() - p.x()).abs() <= SNAP_DISTANCE
+                                && (points[id].y() - p.y()).abs() <= SNAP_DISTANCE
+                        }) {
+                            return id;
+                        }
+                    }
+                }
+            }
+            points.push(*p);
+            buckets.entry(key).or_default().push(points.len() - 1);
+            points.len() - 1
+        };
+        let band_rings = bands
+            .iter()
+            .map(|(rings, _, _)| {
+                rings
+                    .iter()
+                    .map(|ring| {
+                        let mut ids = ring.iter().map(&mut point_id).collect::<Vec<_>>();
+                        ids.dedup();
+                        ids
+                    })
+                    // Degenerated rings are dropped later anyway
+                    .filter(|ids| ids.len() >= 4 && ids[0] == ids[ids.len() - 1])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Find the junctions, where rings meet with different neighbouring points
+        let mut neighbours: FxHashMap<usize, (usize, usize)> = FxHashMap::default();
+        let mut junctions = FxHashSet::default();
+        for ring in band_rings.iter().flatten() {
+            let n = ring.len() - 1;
+            for i in 0..n {
+                let (previous, next) = (ring[(i + n - 1) % n], ring[i + 1]);
+                match neighbours.get(&ring[i]) {
+                    None => {
+                        neighbours.insert(ring[i], (previous, next));
+                    }
+                    Some(&first) => {
+                        if first != (previous, next) && first != (next, previous) {
+                            junctions.insert(ring[i]);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Cut the rings into (deduplicated) arcs
+        let mut arcs: Vec<Vec<usize>> = Vec::new();
+        let mut index: FxHashMap<Vec<usize>, usize> = FxHashMap::default();
+        let mut get_or_insert = |points: Vec<usize>| -> (usize, bool) {
+            if let Some(&i) = index.get(&points) {
+                return (i, false);
+            }
+            let reversed = points.iter().rev().copied().collect::<Vec<_>>();
+            if let Some(&i) = index.get(&reversed) {
+                return (i, true);
+            }
+            index.insert(points.clone(), arcs.len());
+            arcs.push(points);
+            (arcs.len() - 1, false)
+        };
+        let rings = band_rings
+            .into_iter()
+            .map(|rings| {
+                rings
+                    .into_iter()
+                    .map(|ring| {
+                        let n = ring.len() - 1;
+                        match (0..n).find(|&i| junctions.contains(&ring[i])) {
+                            None => {
+                                // The whole ring is an arc, that starts on its smallest point
+                                // so that the same ring always gives the same arc
+                                let start = (0..n).min_by_key(|&i| ring[i]).unwrap();
+                                vec![get_or_insert(
+                                    (0..=n).map(|k| ring[(start + k) % n]).collect(),
+                                )]
+                            }
+                            Some(start) => {
+                                let mut ring_arcs = Vec::new();
+                                let mut arc = vec![ring[start]];
+                                for k in 1..=n {
+                                    let id = ring[(start + k) % n];
+                                    arc.push(id);
+                                    if junctions.contains(&id) {
+                                        ring_arcs.push(get_or_insert(std::mem::replace(
+                                            &mut arc,
+                                            vec![id],
+                                        )));
+                                    }
+                                }
+                                ring_arcs
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        Topology {
+            points,
+            junctions,
+            arcs,
+            rings,
+        }
+    }
+
+    /// Replace the rings of the bands by the rings made of the given arcs
+    /// (the new points of each arc of the topology, in the same direction, the first
+    /// and last points of the arcs that are not whole rings being left unchanged).
+    pub fn write(&self, bands: &mut [BandRaw], arcs: &[Vec<Point<f64>>]) {
+        for ((rings, _, _), band_rings) in bands.iter_mut().zip(self.rings.iter()) {
+            *rings = band_rings
+                .iter()
+                .map(|ring| {
+                    let mut points = Vec::new();
+                    for &(i, reversed) in ring.iter() {
+                        // The first point of an arc is the last point of the previous one
+                        let skip = if points.is_empty() { 0 } else { 1 };
+                        if reversed {
+                            points.extend(arcs[i].iter().rev().skip(skip));
+                        } else {
+                            points.extend(arcs[i].iter().skip(skip));
+                        }
+                    }
+                    points
+                })
+                .collect();
+        }
+    }
+}
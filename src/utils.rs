@@ -19,3 +19,39 @@ pub(crate) fn empty_cell_grid(li: usize, lj: usize) -> Vec<Vec<Option<Cell>>> {
     }
     cell_grid
 }
+
+/// Whether the coordinates are strictly increasing or strictly decreasing.
+pub(crate) fn is_strictly_monotonic(coords: &[f64]) -> bool {
+    coords.windows(2).all(|w| w[0] < w[1]) || coords.windows(2).all(|w| w[0] > w[1])
+}
+
+/// Get the coordinate at the (fractional) index `index`
+/// by linear interpolation between the two surrounding values of `coords`.
+#[inline]
+pub(crate) fn interpolate_coordinate(coords: &[f64], index: f64) -> f64 {
+    if coords.len() < 2 {
+        return coords[0];
+    }
+    let i = (index.floor() as usize).min(coords.len() - 2);
+    coords[i] + (index - i as f64) * (coords[i + 1] - coords[i])
+}
+
+/// Get the value at the (fractional) position (`col`, `row`)
+/// by bilinear interpolation between the four surrounding nodes of the grid `coords`
+/// (stored in row-major order).
+#[inline]
+pub(crate) fn interpolate_bilinear(
+    coords: &[f64],
+    width: usize,
+    height: usize,
+    col: f64,
+    row: f64,
+) -> f64 {
+    let i = (col.floor() as usize).min(width.saturating_sub(2));
+    let j = (row.floor() as usize).min(height.saturating_sub(2));
+    let (i1, j1) = ((i + 1).min(width - 1), (j + 1).min(height - 1));
+    let (fx, fy) = (col - i as f64, row - j as f64);
+    let bottom = coords[j * width + i] + fx * (coords[j * width + i1] - coords[j * width + i]);
+    let top = coords[j1 * width + i] + fx * (coords[j1 * width + i1] - coords[j1 * width + i]);
+    bottom + fy * (top - bottom)
+}
@@ -0,0 +1,145 @@
+use crate::errors::{new_error, ErrorKind, Result};
+use geo_types::{Coord, LineString, MultiPolygon, Polygon};
+
+const LITTLE_ENDIAN: u8 = 1;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOLYGON: u32 = 6;
+
+/// Write a MultiPolygon in WKB format (using little endian byte order).
+/// Polygons without exterior ring are written without rings (their holes being dropped).
+pub(crate) fn write_multipolygon(multipolygon: &MultiPolygon<f64>) -> Vec<u8> {
+    let n_coords: usize = multipolygon
+        .0
+        .iter()
+        .flat_map(rings)
+        .map(|ring| 4 + 16 * ring.0.len())
+        .sum();
+    let mut wkb = Vec::with_capacity(9 + 9 * multipolygon.0.len() + n_coords);
+
+    wkb.push(LITTLE_ENDIAN);
+    wkb.extend_from_slice(&WKB_MULTIPOLYGON.to_le_bytes());
+    wkb.extend_from_slice(&(multipolygon.0.len() as u32).to_le_bytes());
+    for polygon in multipolygon.0.iter() {
+        wkb.push(LITTLE_ENDIAN);
+        wkb.extend_from_slice(&WKB_POLYGON.to_le_bytes());
+        wkb.extend_from_slice(&(rings(polygon).count() as u32).to_le_bytes());
+        for ring in rings(polygon) {
+            wkb.extend_from_slice(&(ring.0.len() as u32).to_le_bytes());
+            for coord in ring.0.iter() {
+                wkb.extend_from_slice(&coord.x.to_le_bytes());
+                wkb.extend_from_slice(&coord.y.to_le_bytes());
+            }
+        }
+    }
+    wkb
+}
+
+/// The rings of a polygon to write: none if it has no exterior ring,
+/// and otherwise its exterior ring and its non-empty interior rings.
+fn rings(polygon: &Polygon<f64>) -> impl Iterator<Item = &LineString<f64>> {
+    let has_exterior = !polygon.exterior().0.is_empty();
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors().iter().filter(|ring| !ring.0.is_empty()))
+        .filter(move |_| has_exterior)
+}
+
+/// Read a MultiPolygon (or a Polygon) in WKB format (using any byte order).
+pub(crate) fn read_multipolygon(wkb: &[u8]) -> Result<MultiPolygon<f64>> {
+    let mut reader = Reader {
+        input: wkb,
+        pos: 0,
+        little_endian: true,
+    };
+    let multipolygon = match reader.header()? {
+        WKB_MULTIPOLYGON => {
+            let n_polygons = reader.u32()?;
+            let mut polygons = Vec::new();
+            for _ in 0..n_polygons {
+                if reader.header()? != WKB_POLYGON {
+                    return Err(reader.error("expected a Polygon"));
+                }
+                polygons.push(reader.polygon()?);
+            }
+            MultiPolygon(polygons)
+        }
+        WKB_POLYGON => MultiPolygon(vec![reader.polygon()?]),
+        _ => return Err(reader.error("expected a MultiPolygon or a Polygon")),
+    };
+    if reader.pos != wkb.len() {
+        return Err(reader.error("unexpected trailing bytes"));
+    }
+    Ok(multipolygon)
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, message: &str) -> crate::errors::Error {
+        new_error(ErrorKind::WkbError(format!(
+            "{} (at byte {})",
+            message, self.pos
+        )))
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self
+            .input
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes::<4>()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        let bytes = self.bytes::<8>()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    /// Read the byte order and the type of a geometry.
+    fn header(&mut self) -> Result<u32> {
+        self.little_endian = match self.bytes::<1>()?[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(self.error("invalid byte order")),
+        };
+        self.u32()
+    }
+
+    fn polygon(&mut self) -> Result<Polygon<f64>> {
+        let n_rings = self.u32()?;
+        if n_rings == 0 {
+            return Ok(Polygon::new(LineString(vec![]), vec![]));
+        }
+        let mut rings = Vec::new();
+        for _ in 0..n_rings {
+            let n_points = self.u32()?;
+            let mut coords = Vec::new();
+            for _ in 0..n_points {
+                coords.push(Coord {
+                    x: self.f64()?,
+                    y: self.f64()?,
+                });
+            }
+            rings.push(LineString(coords));
+        }
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+}
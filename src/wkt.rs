@@ -0,0 +1,173 @@
+use crate::errors::{new_error, ErrorKind, Result};
+use geo_types::{Coord, LineString, MultiPolygon, Polygon};
+use std::fmt::Write;
+
+/// Write a MultiPolygon in WKT format
+/// (coordinates are written with the shortest representation that round-trips).
+/// Polygons without exterior ring are written as `EMPTY` (their holes being dropped).
+pub(crate) fn write_multipolygon(multipolygon: &MultiPolygon<f64>) -> String {
+    if multipolygon.0.is_empty() {
+        return "MULTIPOLYGON EMPTY".to_string();
+    }
+    let mut wkt = String::from("MULTIPOLYGON(");
+    for (i, polygon) in multipolygon.0.iter().enumerate() {
+        if i > 0 {
+            wkt.push(',');
+        }
+        if polygon.exterior().0.is_empty() {
+            wkt.push_str("EMPTY");
+            continue;
+        }
+        wkt.push('(');
+        for (j, ring) in std::iter::once(polygon.exterior())
+            .chain(polygon.interiors().iter().filter(|ring| !ring.0.is_empty()))
+            .enumerate()
+        {
+            if j > 0 {
+                wkt.push(',');
+            }
+            wkt.push('(');
+            for (k, coord) in ring.0.iter().enumerate() {
+                if k > 0 {
+                    wkt.push(',');
+                }
+                write!(wkt, "{} {}", coord.x, coord.y).unwrap();
+            }
+            wkt.push(')');
+        }
+        wkt.push(')');
+    }
+    wkt.push(')');
+    wkt
+}
+
+/// Read a MultiPolygon (or a Polygon) in WKT format.
+pub(crate) fn read_multipolygon(wkt: &str) -> Result<MultiPolygon<f64>> {
+    let mut parser = Parser { input: wkt, pos: 0 };
+    let geometry_type = parser.word()?.to_ascii_uppercase();
+    let multipolygon = match geometry_type.as_str() {
+        "MULTIPOLYGON" => {
+            if parser.empty()? {
+                MultiPolygon(vec![])
+            } else {
+                MultiPolygon(parser.list(Parser::polygon)?)
+            }
+        }
+        "POLYGON" => {
+            if parser.empty()? {
+                MultiPolygon(vec![])
+            } else {
+                MultiPolygon(vec![parser.polygon()?])
+            }
+        }
+        _ => return Err(parser.error("expected MULTIPOLYGON or POLYGON")),
+    };
+    parser.skip_whitespace();
+    if parser.pos != parser.input.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(multipolygon)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> crate::errors::Error {
+        new_error(ErrorKind::WktError(format!(
+            "{} (at position {})",
+            message, self.pos
+        )))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn word(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a keyword"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Consume the `EMPTY` keyword if it is the next token.
+    fn empty(&mut self) -> Result<bool> {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with('(') {
+            Ok(false)
+        } else if self.word()?.eq_ignore_ascii_case("EMPTY") {
+            Ok(true)
+        } else {
+            Err(self.error("expected '(' or EMPTY"))
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// Parse a parenthesized, comma separated, list of items.
+    fn list<T>(&mut self, item: fn(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        loop {
+            self.skip_whitespace();
+            match self.input[self.pos..].chars().next() {
+                Some(',') => {
+                    self.pos += 1;
+                    items.push(item(self)?);
+                }
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(items);
+                }
+                _ => return Err(self.error("expected ',' or ')'")),
+            }
+        }
+    }
+
+    fn polygon(&mut self) -> Result<Polygon<f64>> {
+        if self.empty()? {
+            return Ok(Polygon::new(LineString(vec![]), vec![]));
+        }
+        let mut rings = self.list(|p| p.list(Parser::coord).map(LineString::from))?;
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == ',' || c == ')')
+            .unwrap_or(rest.len());
+        let number = rest[..len]
+            .parse::<f64>()
+            .map_err(|_| self.error("expected a number"))?;
+        self.pos += len;
+        Ok(number)
+    }
+
+    fn coord(&mut self) -> Result<Coord<f64>> {
+        Ok(Coord {
+            x: self.number()?,
+            y: self.number()?,
+        })
+    }
+}